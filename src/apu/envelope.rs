@@ -0,0 +1,62 @@
+const DAC_ENABLE_MASK: u8 = 0b1111_1000;
+
+pub struct Envelope {
+    register: u8,
+    pub volume: u8,
+    timer: u8,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Self {
+            register: 0,
+            volume: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        self.register
+    }
+
+    pub fn write(&mut self, value: u8) {
+        // Bits 7-4: initial volume, bit 3: direction (1 = increase),
+        // bits 2-0: sweep pace in 64 Hz ticks (0 = no sweep)
+        self.register = value;
+    }
+
+    pub fn dac_enabled(&self) -> bool {
+        // The DAC is off if the initial volume is 0 and the direction is decrease
+        self.register & DAC_ENABLE_MASK != 0
+    }
+
+    pub fn trigger(&mut self) {
+        self.volume = self.register >> 4;
+        self.timer = self.period();
+    }
+
+    pub fn clock(&mut self) {
+        let period = self.period();
+        if period == 0 {
+            return;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return;
+        }
+
+        self.timer = period;
+
+        let increase = self.register & 0b0000_1000 != 0;
+        if increase && self.volume < 15 {
+            self.volume += 1;
+        } else if !increase && self.volume > 0 {
+            self.volume -= 1;
+        }
+    }
+
+    fn period(&self) -> u8 {
+        self.register & 0b0000_0111
+    }
+}
@@ -0,0 +1,374 @@
+mod envelope;
+mod length_counter;
+mod noise;
+mod square;
+mod sweep;
+mod wave;
+
+use crate::apu::noise::Noise;
+use crate::apu::square::Square;
+use crate::apu::wave::Wave;
+use crate::machine::CPU_CLOCK_SPEED;
+use crate::model::Model;
+
+pub const NR10: u16 = 0xFF10;
+const NR11: u16 = 0xFF11;
+const NR12: u16 = 0xFF12;
+const NR20: u16 = 0xFF15;
+const NR30: u16 = 0xFF1A;
+const NR40: u16 = 0xFF1F;
+const NR44: u16 = 0xFF23;
+const NR50: u16 = 0xFF24;
+const NR51: u16 = 0xFF25;
+pub const NR52: u16 = 0xFF26;
+
+const WAVE_RAM_START: u16 = 0xFF30;
+pub const WAVE_RAM_END: u16 = 0xFF3F;
+
+const NR52_POWER: u8 = 0b1000_0000;
+const NR52_UNUSED: u8 = 0b0111_0000;
+
+pub const SAMPLE_RATE: u32 = 44_100;
+
+// The frame sequencer runs at 512 Hz and clocks the length
+// counters, the volume envelopes and the frequency sweep
+const FRAME_SEQUENCER_PERIOD: u32 = CPU_CLOCK_SPEED / 512;
+
+// Charge factor of the high-pass filter per T-cycle, which removes
+// the DC offset of the DACs like the capacitor on the real hardware
+const HIGH_PASS_CHARGE: f64 = 0.999958;
+
+trait Channel {
+    fn read_register(&self, register: u16) -> u8;
+    fn write_register(&mut self, register: u16, value: u8);
+    fn tick(&mut self, cycles: u32);
+    fn clock_length(&mut self);
+    fn is_enabled(&self) -> bool;
+    fn dac_enabled(&self) -> bool;
+    // Digital output of the channel in the range 0-15
+    fn output(&self) -> u8;
+}
+
+pub struct Apu {
+    enabled: bool,
+    channel1: Square,
+    channel2: Square,
+    channel3: Wave,
+    channel4: Noise,
+    nr50: u8,
+    nr51: u8,
+    frame_sequencer_step: u8,
+    frame_sequencer_timer: u32,
+    sample_rate: u32,
+    // Fractional position between two output samples, in T-cycles times the sample rate
+    sample_timer: u64,
+    // Sum of the mixed output since the last sample, averaged when resampling
+    accumulator: (f32, f32),
+    accumulated: u32,
+    capacitor: (f32, f32),
+    high_pass_charge: f32,
+    // Interleaved stereo samples in the range -1.0 to 1.0
+    samples: Vec<f32>,
+}
+
+impl Apu {
+    pub fn new(model: Model) -> Self {
+        let mut apu = Self {
+            enabled: true,
+            channel1: Square::new(true),
+            channel2: Square::new(false),
+            channel3: Wave::new(),
+            channel4: Noise::new(),
+            nr50: 0x77,
+            nr51: 0xF3,
+            frame_sequencer_step: 0,
+            frame_sequencer_timer: FRAME_SEQUENCER_PERIOD,
+            sample_rate: 0,
+            sample_timer: 0,
+            accumulator: (0.0, 0.0),
+            accumulated: 0,
+            capacitor: (0.0, 0.0),
+            high_pass_charge: 0.0,
+            samples: Vec::new(),
+        };
+
+        apu.set_sample_rate(SAMPLE_RATE);
+
+        // Channel 1 registers as left behind by the boot chime,
+        // the other registers keep their power-on values
+        apu.write_register(NR10, 0x80);
+        apu.write_register(NR11, 0x80);
+        apu.write_register(NR12, 0xF3);
+
+        if model.has_boot_chime() {
+            apu.channel1.finish_boot_chime();
+        }
+
+        apu
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.sample_timer = 0;
+        self.high_pass_charge =
+            HIGH_PASS_CHARGE.powf(CPU_CLOCK_SPEED as f64 / sample_rate as f64) as f32;
+    }
+
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            NR10..NR20 => self.channel1.read_register(address - NR10),
+            NR20..NR30 => self.channel2.read_register(address - NR20),
+            NR30..NR40 => self.channel3.read_register(address - NR30),
+            NR40..=NR44 => self.channel4.read_register(address - NR40),
+            NR50 => self.nr50,
+            NR51 => self.nr51,
+            NR52 => {
+                let channels = [
+                    self.channel1.is_enabled(),
+                    self.channel2.is_enabled(),
+                    self.channel3.is_enabled(),
+                    self.channel4.is_enabled(),
+                ];
+
+                let status = channels
+                    .iter()
+                    .enumerate()
+                    .fold(0, |status, (index, &enabled)| {
+                        status | ((enabled as u8) << index)
+                    });
+
+                ((self.enabled as u8) << 7) | NR52_UNUSED | status
+            }
+            WAVE_RAM_START..=WAVE_RAM_END => self.channel3.read_wave_ram(address - WAVE_RAM_START),
+            // 0xFF27-0xFF2F are unused
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        // While the APU is powered off, only NR52 and wave RAM are writable
+
+        if !self.enabled && address < NR52 {
+            return;
+        }
+
+        match address {
+            NR10..NR20 => self.channel1.write_register(address - NR10, value),
+            NR20..NR30 => self.channel2.write_register(address - NR20, value),
+            NR30..NR40 => self.channel3.write_register(address - NR30, value),
+            NR40..=NR44 => self.channel4.write_register(address - NR40, value),
+            NR50 => self.nr50 = value,
+            NR51 => self.nr51 = value,
+            NR52 => self.set_power(value & NR52_POWER != 0),
+            WAVE_RAM_START..=WAVE_RAM_END => self
+                .channel3
+                .write_wave_ram(address - WAVE_RAM_START, value),
+            _ => {}
+        }
+    }
+
+    fn set_power(&mut self, enabled: bool) {
+        // Powering off clears all sound registers, powering
+        // on restarts the frame sequencer at step 0
+
+        if self.enabled && !enabled {
+            self.channel1 = Square::new(true);
+            self.channel2 = Square::new(false);
+            self.channel3.reset();
+            self.channel4 = Noise::new();
+            self.nr50 = 0;
+            self.nr51 = 0;
+        } else if !self.enabled && enabled {
+            self.frame_sequencer_step = 0;
+            self.frame_sequencer_timer = FRAME_SEQUENCER_PERIOD;
+        }
+
+        self.enabled = enabled;
+    }
+
+    pub fn tick(&mut self, cycles: u8) {
+        // Advances the channels one M-cycle at a time and
+        // resamples the mixed output to the output sample rate
+
+        for _ in 0..cycles / 4 {
+            if self.enabled {
+                self.step(4);
+            }
+
+            let (left, right) = self.mix();
+            self.accumulator.0 += left;
+            self.accumulator.1 += right;
+            self.accumulated += 1;
+
+            self.sample_timer += self.sample_rate as u64 * 4;
+            if self.sample_timer >= CPU_CLOCK_SPEED as u64 {
+                self.sample_timer -= CPU_CLOCK_SPEED as u64;
+                self.push_sample();
+            }
+        }
+    }
+
+    fn step(&mut self, cycles: u32) {
+        self.channel1.tick(cycles);
+        self.channel2.tick(cycles);
+        self.channel3.tick(cycles);
+        self.channel4.tick(cycles);
+
+        self.frame_sequencer_timer -= cycles;
+        if self.frame_sequencer_timer == 0 {
+            self.frame_sequencer_timer = FRAME_SEQUENCER_PERIOD;
+            self.step_frame_sequencer();
+        }
+    }
+
+    fn step_frame_sequencer(&mut self) {
+        // Step   Length   Envelope   Sweep
+        //  0      Clock      -         -
+        //  2      Clock      -       Clock
+        //  4      Clock      -         -
+        //  6      Clock      -       Clock
+        //  7        -      Clock       -
+
+        if self.frame_sequencer_step.is_multiple_of(2) {
+            self.channel1.clock_length();
+            self.channel2.clock_length();
+            self.channel3.clock_length();
+            self.channel4.clock_length();
+        }
+
+        if self.frame_sequencer_step == 2 || self.frame_sequencer_step == 6 {
+            self.channel1.clock_sweep();
+        }
+
+        if self.frame_sequencer_step == 7 {
+            self.channel1.clock_envelope();
+            self.channel2.clock_envelope();
+            self.channel4.clock_envelope();
+        }
+
+        self.frame_sequencer_step = (self.frame_sequencer_step + 1) % 8;
+    }
+
+    fn mix(&self) -> (f32, f32) {
+        // NR51 routes channels 1-4 to the right (bits 0-3) and the
+        // left (bits 4-7) output, NR50 sets the volume of each side
+
+        let channels: [&dyn Channel; 4] = [
+            &self.channel1,
+            &self.channel2,
+            &self.channel3,
+            &self.channel4,
+        ];
+
+        let mut left = 0.0;
+        let mut right = 0.0;
+
+        for (index, channel) in channels.iter().enumerate() {
+            if !channel.dac_enabled() {
+                continue;
+            }
+
+            // The DAC maps the digital range 0-15 to the analog range 1.0 to -1.0
+            let sample = 1.0 - channel.output() as f32 / 7.5;
+
+            if self.nr51 & (0b0001_0000 << index) != 0 {
+                left += sample;
+            }
+
+            if self.nr51 & (0b0000_0001 << index) != 0 {
+                right += sample;
+            }
+        }
+
+        let left_volume = ((self.nr50 >> 4) & 0b0111) as f32 + 1.0;
+        let right_volume = (self.nr50 & 0b0111) as f32 + 1.0;
+
+        // Four channels at a volume of up to 8
+        (left * left_volume / 32.0, right * right_volume / 32.0)
+    }
+
+    fn push_sample(&mut self) {
+        let count = self.accumulated.max(1) as f32;
+        let left = self.high_pass(self.accumulator.0 / count, true);
+        let right = self.high_pass(self.accumulator.1 / count, false);
+
+        self.accumulator = (0.0, 0.0);
+        self.accumulated = 0;
+
+        // Samples are dropped once a second of audio is buffered,
+        // so a frontend which never drains them does not leak memory
+        if self.samples.len() < self.sample_rate as usize * 2 {
+            self.samples.push(left);
+            self.samples.push(right);
+        }
+    }
+
+    fn high_pass(&mut self, input: f32, left: bool) -> f32 {
+        let capacitor = if left {
+            &mut self.capacitor.0
+        } else {
+            &mut self.capacitor.1
+        };
+
+        let output = input - *capacitor;
+        *capacitor = input - output * self.high_pass_charge;
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NR21: u16 = 0xFF16;
+    const NR22: u16 = 0xFF17;
+    const NR24: u16 = 0xFF19;
+
+    fn run(apu: &mut Apu, cycles: u32) {
+        for _ in 0..cycles / 4 {
+            apu.tick(4);
+        }
+    }
+
+    #[test]
+    fn resamples_to_output_rate() {
+        let mut apu = Apu::new(Model::Dmg);
+        apu.set_sample_rate(48_000);
+
+        run(&mut apu, CPU_CLOCK_SPEED);
+
+        // Interleaved left and right samples
+        assert_eq!(apu.drain_samples().len(), 2 * 48_000);
+        assert!(apu.drain_samples().is_empty());
+    }
+
+    #[test]
+    fn length_counter_disables_channel() {
+        let mut apu = Apu::new(Model::Dmg);
+
+        // Length of 1, full volume, trigger with the length enabled
+        apu.write_register(NR21, 63);
+        apu.write_register(NR22, 0xF0);
+        apu.write_register(NR24, 0xC0);
+        assert_ne!(apu.read_register(NR52) & 0b0010, 0);
+
+        run(&mut apu, 2 * FRAME_SEQUENCER_PERIOD);
+        assert_eq!(apu.read_register(NR52) & 0b0010, 0);
+    }
+
+    #[test]
+    fn power_off_ignores_register_writes() {
+        let mut apu = Apu::new(Model::Dmg);
+
+        apu.write_register(NR52, 0x00);
+        apu.write_register(NR50, 0x77);
+
+        assert_eq!(apu.read_register(NR50), 0x00);
+        assert_eq!(apu.read_register(NR52), NR52_UNUSED);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::apu::envelope::Envelope;
+use crate::apu::length_counter::LengthCounter;
+use crate::apu::Channel;
+
+const LENGTH_MAX: u16 = 64;
+
+const DIVISORS: [u32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+pub struct Noise {
+    length: LengthCounter,
+    envelope: Envelope,
+    enabled: bool,
+    // Bits 7-4: clock shift, bit 3: LFSR width (1 = 7-bit), bits 2-0: divisor code
+    polynomial: u8,
+    lfsr: u16,
+    timer: u32,
+}
+
+impl Noise {
+    pub fn new() -> Self {
+        Self {
+            length: LengthCounter::new(LENGTH_MAX),
+            envelope: Envelope::new(),
+            enabled: false,
+            polynomial: 0,
+            lfsr: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = self.period();
+        self.lfsr = 0x7FFF;
+    }
+
+    fn period(&self) -> u32 {
+        DIVISORS[(self.polynomial & 0b0111) as usize] << (self.polynomial >> 4)
+    }
+
+    fn step_lfsr(&mut self) {
+        // The XOR of the two lowest bits is shifted in at bit 14,
+        // and also at bit 6 in 7-bit mode
+        let bit = (self.lfsr ^ (self.lfsr >> 1)) & 0b1;
+        self.lfsr = (self.lfsr >> 1) | (bit << 14);
+
+        if self.polynomial & 0b0000_1000 != 0 {
+            self.lfsr = (self.lfsr & !(1 << 6)) | (bit << 6);
+        }
+    }
+}
+
+impl Channel for Noise {
+    fn read_register(&self, register: u16) -> u8 {
+        match register {
+            0 => 0xFF,
+            1 => 0xFF,
+            2 => self.envelope.read(),
+            3 => self.polynomial,
+            4 => ((self.length.enabled as u8) << 6) | 0b1011_1111,
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {}
+            1 => self.length.load(value & 0b0011_1111),
+            2 => {
+                self.envelope.write(value);
+
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => self.polynomial = value,
+            4 => {
+                self.length.enabled = value & 0b0100_0000 != 0;
+
+                if value & 0b1000_0000 != 0 {
+                    self.trigger();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        let mut cycles = cycles;
+
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.step_lfsr();
+        }
+
+        self.timer -= cycles;
+    }
+
+    fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    fn output(&self) -> u8 {
+        // The channel outputs a high level when bit 0 of the LFSR is clear
+        if self.enabled && self.lfsr & 0b1 == 0 {
+            self.envelope.volume
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::apu::envelope::Envelope;
+use crate::apu::length_counter::LengthCounter;
+use crate::apu::sweep::Sweep;
+use crate::apu::Channel;
+
+const LENGTH_MAX: u16 = 64;
+
+// Each duty cycle is an 8-step waveform, played from left to right
+const DUTY_PATTERNS: [u8; 4] = [
+    0b0000_0001, // 12.5%
+    0b1000_0001, // 25%
+    0b1000_0111, // 50%
+    0b0111_1110, // 75%
+];
+
+pub struct Square {
+    // Only channel 1 has a frequency sweep unit
+    sweep: Option<Sweep>,
+    length: LengthCounter,
+    envelope: Envelope,
+    enabled: bool,
+    duty: u8,
+    duty_position: u8,
+    frequency: u16,
+    timer: u32,
+}
+
+impl Square {
+    pub fn new(with_sweep: bool) -> Self {
+        Self {
+            sweep: if with_sweep { Some(Sweep::new()) } else { None },
+            length: LengthCounter::new(LENGTH_MAX),
+            envelope: Envelope::new(),
+            enabled: false,
+            duty: 0,
+            duty_position: 0,
+            frequency: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn finish_boot_chime(&mut self) {
+        // The boot chime leaves the channel enabled, but with
+        // its envelope decayed to silence
+        self.enabled = self.envelope.dac_enabled();
+        self.envelope.volume = 0;
+    }
+
+    pub fn clock_envelope(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_sweep(&mut self) {
+        let Some(sweep) = &mut self.sweep else {
+            return;
+        };
+
+        match sweep.clock() {
+            Some(Some(frequency)) => self.frequency = frequency,
+            Some(None) => self.enabled = false,
+            None => {}
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.envelope.dac_enabled();
+        self.length.trigger();
+        self.envelope.trigger();
+        self.timer = self.period();
+
+        if let Some(sweep) = &mut self.sweep {
+            if sweep.trigger(self.frequency) {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn period(&self) -> u32 {
+        (2048 - self.frequency as u32) * 4
+    }
+}
+
+impl Channel for Square {
+    fn read_register(&self, register: u16) -> u8 {
+        // Frequency and length are write-only
+        match register {
+            0 => self.sweep.as_ref().map_or(0xFF, Sweep::read),
+            1 => (self.duty << 6) | 0b0011_1111,
+            2 => self.envelope.read(),
+            3 => 0xFF,
+            4 => ((self.length.enabled as u8) << 6) | 0b1011_1111,
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {
+                if let Some(sweep) = &mut self.sweep {
+                    sweep.write(value);
+                }
+            }
+            1 => {
+                self.duty = value >> 6;
+                self.length.load(value & 0b0011_1111);
+            }
+            2 => {
+                self.envelope.write(value);
+
+                if !self.envelope.dac_enabled() {
+                    self.enabled = false;
+                }
+            }
+            3 => self.frequency = (self.frequency & 0x0700) | value as u16,
+            4 => {
+                self.frequency = (self.frequency & 0x00FF) | ((value as u16 & 0b0111) << 8);
+                self.length.enabled = value & 0b0100_0000 != 0;
+
+                if value & 0b1000_0000 != 0 {
+                    self.trigger();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        // The duty position advances every time the frequency timer expires
+
+        let mut cycles = cycles;
+
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.duty_position = (self.duty_position + 1) % 8;
+        }
+
+        self.timer -= cycles;
+    }
+
+    fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.envelope.dac_enabled()
+    }
+
+    fn output(&self) -> u8 {
+        let high = (DUTY_PATTERNS[self.duty as usize] >> (7 - self.duty_position)) & 0b1 != 0;
+
+        if self.enabled && high {
+            self.envelope.volume
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,97 @@
+pub struct Sweep {
+    register: u8,
+    enabled: bool,
+    shadow_frequency: u16,
+    timer: u8,
+}
+
+impl Sweep {
+    pub fn new() -> Self {
+        Self {
+            register: 0,
+            enabled: false,
+            shadow_frequency: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn read(&self) -> u8 {
+        self.register | 0b1000_0000
+    }
+
+    pub fn write(&mut self, value: u8) {
+        // Bits 6-4: pace, bit 3: direction (1 = decrease), bits 2-0: shift
+        self.register = value & 0b0111_1111;
+    }
+
+    pub fn trigger(&mut self, frequency: u16) -> bool {
+        // Returns whether the initial overflow check disabled the channel
+
+        self.shadow_frequency = frequency;
+        self.timer = self.reload_value();
+        self.enabled = self.pace() != 0 || self.shift() != 0;
+
+        self.shift() != 0 && self.calculate_frequency() > 2047
+    }
+
+    pub fn clock(&mut self) -> Option<Option<u16>> {
+        // Returns None if nothing changed, Some(None) if the frequency
+        // overflowed, which disables the channel, and Some(frequency)
+        // if a new frequency was calculated
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return None;
+        }
+
+        self.timer = self.reload_value();
+
+        if !self.enabled || self.pace() == 0 {
+            return None;
+        }
+
+        let frequency = self.calculate_frequency();
+        if frequency > 2047 {
+            return Some(None);
+        }
+
+        if self.shift() == 0 {
+            return None;
+        }
+
+        self.shadow_frequency = frequency;
+
+        // The new frequency is checked for an overflow once more
+        if self.calculate_frequency() > 2047 {
+            return Some(None);
+        }
+
+        Some(Some(frequency))
+    }
+
+    fn calculate_frequency(&self) -> u16 {
+        let delta = self.shadow_frequency >> self.shift();
+
+        if self.register & 0b0000_1000 != 0 {
+            self.shadow_frequency - delta
+        } else {
+            self.shadow_frequency + delta
+        }
+    }
+
+    fn reload_value(&self) -> u8 {
+        // A pace of 0 is treated as 8
+        match self.pace() {
+            0 => 8,
+            pace => pace,
+        }
+    }
+
+    fn pace(&self) -> u8 {
+        (self.register >> 4) & 0b0111
+    }
+
+    fn shift(&self) -> u8 {
+        self.register & 0b0111
+    }
+}
@@ -0,0 +1,141 @@
+use crate::apu::length_counter::LengthCounter;
+use crate::apu::Channel;
+
+const LENGTH_MAX: u16 = 256;
+const WAVE_RAM_SIZE: usize = 16;
+
+pub struct Wave {
+    // 32 4-bit samples, upper nibble first
+    wave_ram: [u8; WAVE_RAM_SIZE],
+    length: LengthCounter,
+    dac_enabled: bool,
+    enabled: bool,
+    volume: u8,
+    position: u8,
+    frequency: u16,
+    timer: u32,
+}
+
+impl Wave {
+    pub fn new() -> Self {
+        Self {
+            wave_ram: [0; WAVE_RAM_SIZE],
+            length: LengthCounter::new(LENGTH_MAX),
+            dac_enabled: false,
+            enabled: false,
+            volume: 0,
+            position: 0,
+            frequency: 0,
+            timer: 0,
+        }
+    }
+
+    pub fn read_wave_ram(&self, offset: u16) -> u8 {
+        self.wave_ram[offset as usize]
+    }
+
+    pub fn write_wave_ram(&mut self, offset: u16, value: u8) {
+        self.wave_ram[offset as usize] = value;
+    }
+
+    pub fn reset(&mut self) {
+        // Powering the APU off keeps the contents of wave RAM
+        *self = Self {
+            wave_ram: self.wave_ram,
+            ..Self::new()
+        };
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        self.length.trigger();
+        self.timer = self.period();
+        self.position = 0;
+    }
+
+    fn period(&self) -> u32 {
+        (2048 - self.frequency as u32) * 2
+    }
+}
+
+impl Channel for Wave {
+    fn read_register(&self, register: u16) -> u8 {
+        match register {
+            0 => ((self.dac_enabled as u8) << 7) | 0b0111_1111,
+            1 => 0xFF,
+            2 => (self.volume << 5) | 0b1001_1111,
+            3 => 0xFF,
+            4 => ((self.length.enabled as u8) << 6) | 0b1011_1111,
+            _ => unreachable!(),
+        }
+    }
+
+    fn write_register(&mut self, register: u16, value: u8) {
+        match register {
+            0 => {
+                self.dac_enabled = value & 0b1000_0000 != 0;
+
+                if !self.dac_enabled {
+                    self.enabled = false;
+                }
+            }
+            1 => self.length.load(value),
+            2 => self.volume = (value >> 5) & 0b11,
+            3 => self.frequency = (self.frequency & 0x0700) | value as u16,
+            4 => {
+                self.frequency = (self.frequency & 0x00FF) | ((value as u16 & 0b0111) << 8);
+                self.length.enabled = value & 0b0100_0000 != 0;
+
+                if value & 0b1000_0000 != 0 {
+                    self.trigger();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn tick(&mut self, cycles: u32) {
+        let mut cycles = cycles;
+
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period();
+            self.position = (self.position + 1) % 32;
+        }
+
+        self.timer -= cycles;
+    }
+
+    fn clock_length(&mut self) {
+        if self.length.clock() {
+            self.enabled = false;
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn dac_enabled(&self) -> bool {
+        self.dac_enabled
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+
+        let byte = self.wave_ram[self.position as usize / 2];
+        let sample = if self.position.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0F
+        };
+
+        // 0: mute, 1: 100%, 2: 50%, 3: 25%
+        match self.volume {
+            0 => 0,
+            volume => sample >> (volume - 1),
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use crate::cartridge::{RAM_BANK_SIZE, RAM_SIZE_ADDRESS, ROM_BANK_SIZE};
+use crate::cartridge::header::CartridgeHeader;
+use crate::cartridge::{RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+// MBC2 has 512 half-bytes of RAM built in, stored one per byte
+const MBC2_RAM_SIZE: usize = 512;
 
 pub struct Core {
     pub rom_data: Vec<u8>,
@@ -11,8 +15,13 @@ pub struct Core {
 }
 
 impl Core {
-    pub fn new(rom_data: &[u8]) -> Self {
-        let ram_data = create_ram(rom_data);
+    pub fn new(mut rom_data: Vec<u8>, header: &CartridgeHeader) -> Self {
+        // Truncated dumps are padded to the size in the header, so
+        // bank switching never reads past the end of the ROM
+        let rom_size = header.rom_size.max(rom_data.len());
+        rom_data.resize(rom_size, 0xFF);
+
+        let ram_data = create_ram(header);
         let rom_bank = 1;
         let ram_bank = 0;
         let rom_offset: usize = ROM_BANK_SIZE;
@@ -20,7 +29,7 @@ impl Core {
         let ram_enabled = false;
 
         Self {
-            rom_data: rom_data.to_vec(),
+            rom_data,
             ram_data,
             rom_bank,
             ram_bank,
@@ -31,16 +40,16 @@ impl Core {
     }
 }
 
-fn create_ram(rom_data: &[u8]) -> Option<Vec<u8>> {
-    let ram_size = match rom_data[RAM_SIZE_ADDRESS] {
-        0x00 => None,
-        0x01 => None,
-        0x02 => Some(8 * 1024),
-        0x03 => Some(32 * 1024),
-        0x04 => Some(128 * 1024),
-        0x05 => Some(64 * 1024),
-        _ => None,
-    };
+fn create_ram(header: &CartridgeHeader) -> Option<Vec<u8>> {
+    // MBC2 cartridges report no RAM in the header, even though the
+    // controller contains its own, which is battery-backed on 0x06
+    if let 0x05 | 0x06 = header.cartridge_type {
+        return Some(vec![0; MBC2_RAM_SIZE]);
+    }
 
-    ram_size.map(|size| vec![0; size])
+    // The 2 KiB chip is mirrored, so it is allocated as a full bank
+    match header.ram_size {
+        0 => None,
+        size => Some(vec![0; size.max(RAM_BANK_SIZE)]),
+    }
 }
@@ -0,0 +1,341 @@
+use std::fmt;
+
+pub const LOGO_START: usize = 0x0104;
+pub const LOGO_END: usize = 0x0134;
+const TITLE_START: usize = 0x0134;
+const TITLE_END: usize = 0x0144;
+const MANUFACTURER_START: usize = 0x013F;
+const MANUFACTURER_END: usize = 0x0143;
+const CGB_FLAG_ADDRESS: usize = 0x0143;
+const NEW_LICENSEE_START: usize = 0x0144;
+const NEW_LICENSEE_END: usize = 0x0146;
+const SGB_FLAG_ADDRESS: usize = 0x0146;
+const CARTRIDGE_TYPE_ADDRESS: usize = 0x0147;
+const ROM_SIZE_ADDRESS: usize = 0x0148;
+const RAM_SIZE_ADDRESS: usize = 0x0149;
+const OLD_LICENSEE_ADDRESS: usize = 0x014B;
+const VERSION_ADDRESS: usize = 0x014C;
+const HEADER_CHECKSUM_ADDRESS: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDRESS: usize = 0x014E;
+pub const HEADER_END: usize = 0x0150;
+
+// An old licensee code of 0x33 means the new licensee code is used instead
+const USE_NEW_LICENSEE: u8 = 0x33;
+
+// The boot ROM compares this bitmap and locks up if it doesn't match
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CgbSupport {
+    // 0x80: works on DMG as well, 0xC0: CGB only
+    None,
+    Compatible,
+    Only,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeaderError {
+    TooSmall(usize),
+    UnknownRomSize(u8),
+    UnknownRamSize(u8),
+    UnsupportedCartridgeType(u8),
+    RomSizeMismatch { header: usize, actual: usize },
+    InvalidLogo,
+    HeaderChecksumMismatch { header: u8, computed: u8 },
+    GlobalChecksumMismatch { header: u16, computed: u16 },
+}
+
+impl HeaderError {
+    pub fn is_fatal(&self) -> bool {
+        // The logo and header checksum are verified by the boot ROM,
+        // but many homebrew ROMs and patches get them wrong, so only
+        // problems which make the cartridge unusable are fatal
+        matches!(
+            self,
+            HeaderError::TooSmall(_)
+                | HeaderError::UnknownRomSize(_)
+                | HeaderError::UnknownRamSize(_)
+                | HeaderError::UnsupportedCartridgeType(_)
+        )
+    }
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeaderError::TooSmall(size) => write!(
+                f,
+                "ROM is {} bytes, too small to contain a cartridge header",
+                size
+            ),
+            HeaderError::UnknownRomSize(code) => write!(f, "Unknown ROM size code 0x{:02X}", code),
+            HeaderError::UnknownRamSize(code) => write!(f, "Unknown RAM size code 0x{:02X}", code),
+            HeaderError::UnsupportedCartridgeType(code) => {
+                write!(f, "Cartridge type 0x{:02X} not supported", code)
+            }
+            HeaderError::RomSizeMismatch { header, actual } => write!(
+                f,
+                "ROM is {} bytes, but the header declares {} bytes",
+                actual, header
+            ),
+            HeaderError::InvalidLogo => write!(f, "Nintendo logo doesn't match"),
+            HeaderError::HeaderChecksumMismatch { header, computed } => write!(
+                f,
+                "Header checksum is 0x{:02X}, but should be 0x{:02X}",
+                header, computed
+            ),
+            HeaderError::GlobalChecksumMismatch { header, computed } => write!(
+                f,
+                "Global checksum is 0x{:04X}, but should be 0x{:04X}",
+                header, computed
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CartridgeHeader {
+    pub title: String,
+    // Sum of the title bytes, which the CGB boot ROM picks
+    // a palette for Nintendo's DMG games with
+    pub title_checksum: u8,
+    // Only present on newer cartridges, which use part of the title area
+    pub manufacturer: Option<String>,
+    pub cgb: CgbSupport,
+    pub sgb: bool,
+    pub new_licensee_code: String,
+    pub old_licensee_code: u8,
+    pub cartridge_type: u8,
+    pub rom_size: usize,
+    pub ram_size: usize,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+    pub logo_valid: bool,
+    computed_header_checksum: u8,
+    computed_global_checksum: u16,
+}
+
+impl CartridgeHeader {
+    pub fn parse(rom_data: &[u8]) -> Result<Self, HeaderError> {
+        if rom_data.len() < HEADER_END {
+            return Err(HeaderError::TooSmall(rom_data.len()));
+        }
+
+        let cgb = match rom_data[CGB_FLAG_ADDRESS] {
+            0xC0 => CgbSupport::Only,
+            flag if flag & 0x80 != 0 => CgbSupport::Compatible,
+            _ => CgbSupport::None,
+        };
+
+        let (title_end, manufacturer) = if cgb == CgbSupport::None {
+            (TITLE_END, None)
+        } else {
+            (
+                MANUFACTURER_START,
+                Some(read_string(&rom_data[MANUFACTURER_START..MANUFACTURER_END])),
+            )
+        };
+
+        let rom_size_code = rom_data[ROM_SIZE_ADDRESS];
+        let rom_size = match rom_size_code {
+            // 32 KiB shifted left by the code
+            0x00..=0x08 => (32 * 1024) << rom_size_code,
+            _ => return Err(HeaderError::UnknownRomSize(rom_size_code)),
+        };
+
+        let ram_size_code = rom_data[RAM_SIZE_ADDRESS];
+        let ram_size = match ram_size_code {
+            0x00 => 0,
+            0x01 => 2 * 1024,
+            0x02 => 8 * 1024,
+            0x03 => 32 * 1024,
+            0x04 => 128 * 1024,
+            0x05 => 64 * 1024,
+            _ => return Err(HeaderError::UnknownRamSize(ram_size_code)),
+        };
+
+        Ok(Self {
+            title: read_string(&rom_data[TITLE_START..title_end]),
+            title_checksum: rom_data[TITLE_START..TITLE_END]
+                .iter()
+                .fold(0u8, |sum, &byte| sum.wrapping_add(byte)),
+            manufacturer,
+            cgb,
+            sgb: rom_data[SGB_FLAG_ADDRESS] == 0x03,
+            new_licensee_code: read_string(&rom_data[NEW_LICENSEE_START..NEW_LICENSEE_END]),
+            old_licensee_code: rom_data[OLD_LICENSEE_ADDRESS],
+            cartridge_type: rom_data[CARTRIDGE_TYPE_ADDRESS],
+            rom_size,
+            ram_size,
+            version: rom_data[VERSION_ADDRESS],
+            header_checksum: rom_data[HEADER_CHECKSUM_ADDRESS],
+            global_checksum: u16::from_be_bytes([
+                rom_data[GLOBAL_CHECKSUM_ADDRESS],
+                rom_data[GLOBAL_CHECKSUM_ADDRESS + 1],
+            ]),
+            logo_valid: rom_data[LOGO_START..LOGO_END] == NINTENDO_LOGO,
+            computed_header_checksum: header_checksum(rom_data),
+            computed_global_checksum: global_checksum(rom_data),
+        })
+    }
+
+    pub fn validate(&self, rom_data: &[u8]) -> Vec<HeaderError> {
+        // Returns every problem found, an empty list means a good dump
+
+        let mut errors = Vec::new();
+
+        if rom_data.len() != self.rom_size {
+            errors.push(HeaderError::RomSizeMismatch {
+                header: self.rom_size,
+                actual: rom_data.len(),
+            });
+        }
+
+        if !self.logo_valid {
+            errors.push(HeaderError::InvalidLogo);
+        }
+
+        if self.header_checksum != self.computed_header_checksum {
+            errors.push(HeaderError::HeaderChecksumMismatch {
+                header: self.header_checksum,
+                computed: self.computed_header_checksum,
+            });
+        }
+
+        if self.global_checksum != self.computed_global_checksum {
+            errors.push(HeaderError::GlobalChecksumMismatch {
+                header: self.global_checksum,
+                computed: self.computed_global_checksum,
+            });
+        }
+
+        errors
+    }
+
+    pub fn licensee_code(&self) -> String {
+        if self.old_licensee_code == USE_NEW_LICENSEE {
+            self.new_licensee_code.clone()
+        } else {
+            format!("{:02X}", self.old_licensee_code)
+        }
+    }
+}
+
+fn read_string(bytes: &[u8]) -> String {
+    // Strings are padded with 0x00, anything that isn't
+    // printable ASCII is dropped
+    bytes
+        .iter()
+        .take_while(|&&byte| byte != 0x00)
+        .filter(|byte| byte.is_ascii_graphic() || **byte == b' ')
+        .map(|&byte| byte as char)
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+fn header_checksum(rom_data: &[u8]) -> u8 {
+    rom_data[TITLE_START..HEADER_CHECKSUM_ADDRESS]
+        .iter()
+        .fold(0u8, |checksum, &byte| {
+            checksum.wrapping_sub(byte).wrapping_sub(1)
+        })
+}
+
+fn global_checksum(rom_data: &[u8]) -> u16 {
+    // Sum of all bytes, except for the checksum itself
+    rom_data
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            *index != GLOBAL_CHECKSUM_ADDRESS && *index != GLOBAL_CHECKSUM_ADDRESS + 1
+        })
+        .fold(0u16, |checksum, (_, &byte)| {
+            checksum.wrapping_add(byte as u16)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::{test_rom, Cartridge};
+
+    fn valid_rom() -> Vec<u8> {
+        let mut rom_data = test_rom(0x00, 0x00, 0x00);
+        rom_data[LOGO_START..LOGO_END].copy_from_slice(&NINTENDO_LOGO);
+        rom_data[TITLE_START..TITLE_START + 6].copy_from_slice(b"GEMBOI");
+        rom_data[HEADER_CHECKSUM_ADDRESS] = header_checksum(&rom_data);
+
+        let checksum = global_checksum(&rom_data);
+        rom_data[GLOBAL_CHECKSUM_ADDRESS..GLOBAL_CHECKSUM_ADDRESS + 2]
+            .copy_from_slice(&checksum.to_be_bytes());
+
+        rom_data
+    }
+
+    #[test]
+    fn header_checksum_covers_title_to_version() {
+        // Subtracting 25 zero bytes plus one each time
+        assert_eq!(header_checksum(&test_rom(0x00, 0x00, 0x00)), 0xE7);
+    }
+
+    #[test]
+    fn valid_rom_has_no_errors() {
+        let rom_data = valid_rom();
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+
+        assert_eq!(header.title, "GEMBOI");
+        assert_eq!(header.cgb, CgbSupport::None);
+        assert_eq!(header.rom_size, 32 * 1024);
+        assert!(header.validate(&rom_data).is_empty());
+    }
+
+    #[test]
+    fn validate_reports_every_mismatch() {
+        let mut rom_data = valid_rom();
+        rom_data[LOGO_START] = 0x00;
+        rom_data[TITLE_START] = b'X';
+        rom_data.truncate(0x4000);
+
+        let header = CartridgeHeader::parse(&rom_data).unwrap();
+        let errors = header.validate(&rom_data);
+
+        assert_eq!(errors.len(), 4);
+        assert_eq!(
+            errors[0],
+            HeaderError::RomSizeMismatch {
+                header: 0x8000,
+                actual: 0x4000
+            }
+        );
+        assert_eq!(errors[1], HeaderError::InvalidLogo);
+        assert!(errors.iter().all(|error| !error.is_fatal()));
+    }
+
+    #[test]
+    fn unusable_headers_are_errors() {
+        assert_eq!(
+            CartridgeHeader::parse(&[0; 0x100]).unwrap_err(),
+            HeaderError::TooSmall(0x100)
+        );
+
+        let mut rom_data = valid_rom();
+        rom_data[ROM_SIZE_ADDRESS] = 0x52;
+        assert_eq!(
+            CartridgeHeader::parse(&rom_data).unwrap_err(),
+            HeaderError::UnknownRomSize(0x52)
+        );
+
+        let mut rom_data = valid_rom();
+        rom_data[CARTRIDGE_TYPE_ADDRESS] = 0xFC;
+        assert_eq!(
+            Cartridge::build(rom_data).err(),
+            Some(HeaderError::UnsupportedCartridgeType(0xFC))
+        );
+    }
+}
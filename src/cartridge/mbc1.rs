@@ -1,5 +1,10 @@
 use crate::cartridge::core::Core;
-use crate::cartridge::{MemoryBankController, MASK_MSB, RAM_ADDRESS};
+use crate::cartridge::header::{LOGO_END, LOGO_START, NINTENDO_LOGO};
+use crate::cartridge::{MemoryBankController, MASK_MSB, RAM_ADDRESS, RAM_BANK_SIZE, ROM_BANK_SIZE};
+
+// MBC1M multicarts are 1 MiB collections of 256 KiB games
+const MULTICART_ROM_SIZE: usize = 1024 * 1024;
+const MULTICART_GAME_BANK: usize = 0x10;
 
 enum Mode {
     RomBanking,
@@ -8,27 +13,75 @@ enum Mode {
 
 pub struct Mbc1 {
     mode: Mode,
+    // Lower 5 bits of the ROM bank number, 0 is translated to 1
+    bank1: u8,
+    // 2-bit register, either upper ROM bank bits or the RAM bank number
+    bank2: u8,
+    // Multicarts wire only 4 bits of bank1, so bank2 starts at bit 4
+    multicart: bool,
 }
 
 impl Mbc1 {
-    pub fn new() -> Self {
+    pub fn new(rom_data: &[u8]) -> Self {
         Self {
             mode: Mode::RomBanking,
+            bank1: 1,
+            bank2: 0,
+            multicart: is_multicart(rom_data),
+        }
+    }
+
+    fn bank2_shift(&self) -> u8 {
+        if self.multicart {
+            4
+        } else {
+            5
+        }
+    }
+
+    fn lower_rom_bank(&self) -> usize {
+        // In mode 1, bank2 also switches 0x0000-0x3FFF on large carts
+        match self.mode {
+            Mode::RomBanking => 0,
+            Mode::RamBanking => (self.bank2 << self.bank2_shift()) as usize,
+        }
+    }
+
+    fn upper_rom_bank(&self) -> usize {
+        let bank1 = if self.multicart {
+            self.bank1 & 0b0000_1111
+        } else {
+            self.bank1
+        };
+
+        ((self.bank2 << self.bank2_shift()) | bank1) as usize
+    }
+
+    fn ram_bank(&self) -> usize {
+        match self.mode {
+            Mode::RomBanking => 0,
+            Mode::RamBanking => self.bank2 as usize,
         }
     }
 }
 
 impl MemoryBankController for Mbc1 {
     fn read_rom(&self, core: &Core, address: u16) -> u8 {
+        // Bank numbers wrap around at the ROM size
+        let bank_count = (core.rom_data.len() / ROM_BANK_SIZE).max(1);
+
         match (address & MASK_MSB) >> 12 {
             // 0x0000 - 0x3FFF
-            // Bank 00 (read-only)
-            0x0..=0x3 => core.rom_data[address as usize],
+            // Bank 00, or 20/40/60 in mode 1 (read-only)
+            0x0..=0x3 => {
+                let offset = ROM_BANK_SIZE * (self.lower_rom_bank() % bank_count);
+                core.rom_data[address as usize + offset]
+            }
             // 0x4000 - 0x7FFF
             // Bank 01-7F (read-only)
             0x4..=0x7 => {
-                let offset = core.rom_offset * core.rom_bank as usize;
-                core.rom_data[(address as usize - core.rom_offset) + offset]
+                let offset = ROM_BANK_SIZE * (self.upper_rom_bank() % bank_count);
+                core.rom_data[(address as usize - ROM_BANK_SIZE) + offset]
             }
             _ => panic!("Address unknown: 0x{:#X}", address),
         }
@@ -44,28 +97,25 @@ impl MemoryBankController for Mbc1 {
             // 0x2000 - 0x3FFF
             // ROM bank number (write-only)
             0x2 | 0x3 => {
-                // Specify the lower 5 bits
-                let bank_number = if value == 0 { 1 } else { value };
-                core.rom_bank = (core.rom_bank & 0b0110_0000) | (bank_number & 0b0001_1111);
+                // Specify the lower 5 bits, the zero check
+                // uses all 5 bits even on multicarts
+                let bank_number = value & 0b0001_1111;
+                self.bank1 = if bank_number == 0 { 1 } else { bank_number };
             }
             // 0x4000 - 0x5FFF
             // RAM bank number — or — upper bits of ROM bank number (write-only)
-            0x4 | 0x5 => match self.mode {
-                Mode::RamBanking => {
-                    core.ram_bank = value & 0b0000_0011;
-                }
-                Mode::RomBanking => {
-                    // Specify the upper two bits (5 and 6) of the ROM bank number
-                    core.rom_bank |= (value & 0b0000_0011) << 5;
-                }
-            },
+            0x4 | 0x5 => {
+                self.bank2 = value & 0b0000_0011;
+            }
             // 0x6000 - 0x7FFF
             // Banking mode select (write-only)
-            0x6 | 0x7 => match value {
-                0 => self.mode = Mode::RomBanking,
-                1 => self.mode = Mode::RamBanking,
-                _ => {}
-            },
+            0x6 | 0x7 => {
+                self.mode = if value & 0b1 == 0 {
+                    Mode::RomBanking
+                } else {
+                    Mode::RamBanking
+                };
+            }
             _ => println!(
                 "Writing to unknown Cartridge ROM location 0x{:04x}",
                 address
@@ -73,15 +123,20 @@ impl MemoryBankController for Mbc1 {
         }
     }
 
-    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) {
+    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) -> bool {
         if !core.ram_enabled {
-            return;
+            return false;
         }
 
+        let offset = RAM_BANK_SIZE * self.ram_bank() + (address as usize - RAM_ADDRESS);
+
         if let Some(ref mut ram_data) = core.ram_data {
-            let offset = core.ram_offset * core.ram_bank as usize;
-            ram_data[(address as usize - RAM_ADDRESS) + offset] = value;
+            let index = offset % ram_data.len();
+            ram_data[index] = value;
+            return true;
         }
+
+        false
     }
 
     fn read_ram(&self, core: &Core, address: u16) -> u8 {
@@ -89,11 +144,107 @@ impl MemoryBankController for Mbc1 {
             return 0xFF;
         }
 
+        let offset = RAM_BANK_SIZE * self.ram_bank() + (address as usize - RAM_ADDRESS);
+
         if let Some(ref ram_data) = core.ram_data {
-            let offset = core.ram_offset * core.ram_bank as usize;
-            return ram_data[(address as usize - RAM_ADDRESS) + offset];
+            return ram_data[offset % ram_data.len()];
         }
 
         0xFF
     }
 }
+
+fn is_multicart(rom_data: &[u8]) -> bool {
+    // Multicarts can't be told apart by their header, but every
+    // game has its own header with the Nintendo logo, the
+    // second one starting at bank 0x10
+
+    if rom_data.len() != MULTICART_ROM_SIZE {
+        return false;
+    }
+
+    let game_offset = ROM_BANK_SIZE * MULTICART_GAME_BANK;
+
+    rom_data[game_offset + LOGO_START..game_offset + LOGO_END] == NINTENDO_LOGO
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cartridge::{test_rom, Cartridge};
+
+    fn mark_banks(rom_data: &mut [u8]) {
+        // The first byte of every bank holds its number
+        for (bank, data) in rom_data.chunks_exact_mut(ROM_BANK_SIZE).enumerate() {
+            data[0] = bank as u8;
+        }
+    }
+
+    #[test]
+    fn zero_check_uses_all_5_bits() {
+        let mut rom_data = test_rom(0x01, 0x06, 0x00);
+        mark_banks(&mut rom_data);
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 0x01);
+
+        // 0x20 has the lower 5 bits clear, but upper bits come from bank2
+        cartridge.write(0x2000, 0xE0);
+        cartridge.write(0x4000, 0x01);
+        assert_eq!(cartridge.read(0x4000), 0x21);
+    }
+
+    #[test]
+    fn bank_number_wraps_by_rom_size() {
+        // 8 banks, so 0x1A maps bank 0x02
+        let mut rom_data = test_rom(0x01, 0x02, 0x00);
+        mark_banks(&mut rom_data);
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x1A);
+        assert_eq!(cartridge.read(0x4000), 0x02);
+    }
+
+    #[test]
+    fn mode_1_banks_lower_window_and_ram() {
+        let mut rom_data = test_rom(0x03, 0x06, 0x03);
+        mark_banks(&mut rom_data);
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, 0x02);
+        cartridge.write(0xA000, 0x42);
+        assert_eq!(cartridge.read(0x0000), 0x00);
+
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(cartridge.read(0x0000), 0x40);
+        assert_ne!(cartridge.read(0xA000), 0x42);
+    }
+
+    #[test]
+    fn multicart_uses_4_bit_bank_wiring() {
+        let mut rom_data = test_rom(0x01, 0x05, 0x00);
+        mark_banks(&mut rom_data);
+        for game in [0x00, 0x10] {
+            let offset = game * ROM_BANK_SIZE;
+            rom_data[offset + LOGO_START..offset + LOGO_END].copy_from_slice(&NINTENDO_LOGO);
+        }
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x12);
+        cartridge.write(0x4000, 0x01);
+        assert_eq!(cartridge.read(0x4000), 0x12);
+    }
+
+    #[test]
+    fn blank_second_logo_is_no_multicart() {
+        let mut rom_data = test_rom(0x01, 0x05, 0x00);
+        mark_banks(&mut rom_data);
+        rom_data[LOGO_START..LOGO_END].copy_from_slice(&NINTENDO_LOGO);
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x12);
+        cartridge.write(0x4000, 0x01);
+        assert_eq!(cartridge.read(0x4000), 0x32);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::cartridge::core::Core;
+use crate::cartridge::{MemoryBankController, MASK_MSB, ROM_BANK_SIZE};
+
+// The 512 half-bytes of built-in RAM repeat throughout 0xA000-0xBFFF
+const RAM_ADDRESS_MASK: u16 = 0x01FF;
+const RAM_UPPER_NIBBLE: u8 = 0xF0;
+
+// Address bit 8 selects between the RAM enable and the ROM bank register
+const REGISTER_SELECT: u16 = 0x0100;
+
+pub struct Mbc2 {}
+
+impl Mbc2 {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl MemoryBankController for Mbc2 {
+    fn read_rom(&self, core: &Core, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF
+            // Bank 00 (read-only)
+            0x0..=0x3 => core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF
+            // Bank 01-0F (read-only)
+            0x4..=0x7 => {
+                let bank_count = (core.rom_data.len() / ROM_BANK_SIZE).max(1);
+                let offset = ROM_BANK_SIZE * (core.rom_bank as usize % bank_count);
+                core.rom_data[(address as usize - ROM_BANK_SIZE) + offset]
+            }
+            _ => panic!("Address unknown: 0x{:#X}", address),
+        }
+    }
+
+    fn write_rom(&mut self, core: &mut Core, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF
+            // RAM enable if bit 8 of the address is clear,
+            // ROM bank number otherwise (write-only)
+            0x0..=0x3 => {
+                if address & REGISTER_SELECT == 0 {
+                    core.ram_enabled = (value & 0x0F) == 0x0A;
+                } else {
+                    let bank_number = value & 0x0F;
+                    core.rom_bank = if bank_number == 0 { 1 } else { bank_number };
+                }
+            }
+            // 0x4000 - 0x7FFF
+            // No registers
+            0x4..=0x7 => {}
+            _ => println!(
+                "Writing to unknown Cartridge ROM location 0x{:04x}",
+                address
+            ),
+        }
+    }
+
+    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) -> bool {
+        // Only the lower 4 bits are stored
+
+        if !core.ram_enabled {
+            return false;
+        }
+
+        if let Some(ref mut ram_data) = core.ram_data {
+            ram_data[(address & RAM_ADDRESS_MASK) as usize] = value & 0x0F;
+            return true;
+        }
+
+        false
+    }
+
+    fn read_ram(&self, core: &Core, address: u16) -> u8 {
+        // The upper 4 bits are undefined and read as 1s
+
+        if !core.ram_enabled {
+            return 0xFF;
+        }
+
+        if let Some(ref ram_data) = core.ram_data {
+            return ram_data[(address & RAM_ADDRESS_MASK) as usize] | RAM_UPPER_NIBBLE;
+        }
+
+        0xFF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cartridge::{test_rom, Cartridge, ROM_BANK_SIZE};
+
+    #[test]
+    fn address_bit_8_selects_register() {
+        let mut rom_data = test_rom(0x05, 0x03, 0x00);
+        rom_data[0x0F * ROM_BANK_SIZE] = 0x0F;
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        // Bit 8 clear enables RAM instead of switching banks
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x2100, 0x0F);
+
+        assert_eq!(cartridge.read(0x4000), 0x0F);
+        assert_eq!(cartridge.read(0xA000), 0xF0);
+    }
+
+    #[test]
+    fn ram_stores_lower_nibble_and_repeats() {
+        let mut cartridge = Cartridge::build(test_rom(0x06, 0x01, 0x00)).unwrap();
+        cartridge.write(0x0000, 0x0A);
+
+        cartridge.write(0xA1FF, 0x3C);
+        assert_eq!(cartridge.read(0xBFFF), 0xFC);
+    }
+}
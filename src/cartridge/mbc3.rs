@@ -1,11 +1,23 @@
 use crate::cartridge::core::Core;
+use crate::cartridge::rtc::{Rtc, RTC_DAY_HIGH, RTC_SECONDS};
 use crate::cartridge::{MemoryBankController, MASK_MSB, RAM_ADDRESS};
 
-pub struct Mbc3 {}
+pub struct Mbc3 {
+    // Only cartridge types 0x0F and 0x10 have the timer
+    rtc: Option<Rtc>,
+    // RTC register mapped into 0xA000-0xBFFF instead of a RAM bank
+    rtc_register: Option<u8>,
+    // The clock is latched by writing 0x00 and then 0x01
+    latch_value: u8,
+}
 
 impl Mbc3 {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(has_rtc: bool) -> Self {
+        Self {
+            rtc: if has_rtc { Some(Rtc::new()) } else { None },
+            rtc_register: None,
+            latch_value: 0xFF,
+        }
     }
 }
 
@@ -18,7 +30,8 @@ impl MemoryBankController for Mbc3 {
             // 0x4000 - 0x7FFF
             // Bank 01-7F (read-only)
             0x4..=0x7 => {
-                let offset = core.rom_offset * core.rom_bank as usize;
+                let bank_count = (core.rom_data.len() / core.rom_offset).max(1);
+                let offset = core.rom_offset * (core.rom_bank as usize % bank_count);
                 core.rom_data[(address as usize - core.rom_offset) + offset]
             }
             _ => panic!("Address unknown: 0x{:#X}", address),
@@ -28,21 +41,39 @@ impl MemoryBankController for Mbc3 {
     fn write_rom(&mut self, core: &mut Core, address: u16, value: u8) {
         match (address & MASK_MSB) >> 12 {
             // 0x0000 - 0x1FFF
-            // RAM enable (write-only)
+            // RAM and timer enable (write-only)
             0x0 | 0x1 => {
                 core.ram_enabled = (value & 0x0F) == 0x0A;
             }
             // 0x2000 - 0x3FFF
             // ROM bank number (write-only)
             0x2 | 0x3 => {
-                // Specify the lower 5 bits
-                let bank_number = if value == 0 { 1 } else { value };
-                core.rom_bank = (core.rom_bank & 0b0110_0000) | (bank_number & 0b0111_1111);
+                // All 7 bits are written at once
+                let bank_number = value & 0b0111_1111;
+                core.rom_bank = if bank_number == 0 { 1 } else { bank_number };
             }
             // 0x4000 - 0x5FFF
-            // RAM bank number (write-only)
-            0x4 | 0x5 => {
-                core.ram_bank = value & 0b0000_0011;
+            // RAM bank number — or — RTC register select (write-only)
+            0x4 | 0x5 => match value {
+                0x00..=0x03 => {
+                    core.ram_bank = value;
+                    self.rtc_register = None;
+                }
+                RTC_SECONDS..=RTC_DAY_HIGH if self.rtc.is_some() => {
+                    self.rtc_register = Some(value);
+                }
+                _ => {}
+            },
+            // 0x6000 - 0x7FFF
+            // Latch clock data (write-only)
+            0x6 | 0x7 => {
+                if self.latch_value == 0x00 && value == 0x01 {
+                    if let Some(ref mut rtc) = self.rtc {
+                        rtc.latch();
+                    }
+                }
+
+                self.latch_value = value;
             }
             _ => println!(
                 "Writing to unknown Cartridge ROM location 0x{:04x}",
@@ -51,15 +82,23 @@ impl MemoryBankController for Mbc3 {
         }
     }
 
-    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) {
+    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) -> bool {
         if !core.ram_enabled {
-            return;
+            return false;
+        }
+
+        if let (Some(register), Some(ref mut rtc)) = (self.rtc_register, &mut self.rtc) {
+            rtc.write(register, value);
+            return true;
         }
 
         if let Some(ref mut ram_data) = core.ram_data {
-            let offset = core.ram_offset * core.ram_bank as usize;
-            ram_data[(address as usize - RAM_ADDRESS) + offset] = value;
+            let index = ram_index(core.ram_offset, core.ram_bank, ram_data.len(), address);
+            ram_data[index] = value;
+            return true;
         }
+
+        false
     }
 
     fn read_ram(&self, core: &Core, address: u16) -> u8 {
@@ -67,11 +106,81 @@ impl MemoryBankController for Mbc3 {
             return 0xFF;
         }
 
+        if let (Some(register), Some(ref rtc)) = (self.rtc_register, &self.rtc) {
+            return rtc.read(register);
+        }
+
         if let Some(ref ram_data) = core.ram_data {
-            let offset = core.ram_offset * core.ram_bank as usize;
-            return ram_data[(address as usize - RAM_ADDRESS) + offset];
+            return ram_data[ram_index(core.ram_offset, core.ram_bank, ram_data.len(), address)];
         }
 
         0xFF
     }
+
+    fn tick(&mut self, cycles: u8) {
+        if let Some(ref mut rtc) = self.rtc {
+            rtc.tick(cycles);
+        }
+    }
+
+    fn rtc(&self) -> Option<&Rtc> {
+        self.rtc.as_ref()
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        self.rtc.as_mut()
+    }
+}
+
+fn ram_index(ram_offset: usize, ram_bank: u8, ram_size: usize, address: u16) -> usize {
+    // Banks beyond the RAM size wrap around
+    let offset = ram_offset * ram_bank as usize + (address as usize - RAM_ADDRESS);
+    offset % ram_size
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cartridge::rtc::{RTC_MINUTES, RTC_SECONDS};
+    use crate::cartridge::{test_rom, Cartridge};
+
+    #[test]
+    fn clock_is_latched_by_writing_0_then_1() {
+        let mut cartridge = Cartridge::build(test_rom(0x10, 0x01, 0x03)).unwrap();
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0x4000, RTC_MINUTES);
+        cartridge.write(0xA000, 0x2A);
+
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+
+        cartridge.write(0x6000, 0x00);
+        cartridge.write(0x6000, 0x01);
+        assert_eq!(cartridge.read(0xA000), 0x2A);
+
+        // Switching back to a RAM bank unmaps the register
+        cartridge.write(0x4000, 0x00);
+        cartridge.write(0xA000, 0x11);
+        cartridge.write(0x4000, RTC_SECONDS);
+        assert_eq!(cartridge.read(0xA000), 0x00);
+    }
+
+    #[test]
+    fn rtc_registers_need_the_timer() {
+        let mut cartridge = Cartridge::build(test_rom(0x13, 0x01, 0x03)).unwrap();
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0xA000, 0x42);
+
+        cartridge.write(0x4000, RTC_SECONDS);
+        assert_eq!(cartridge.read(0xA000), 0x42);
+    }
+
+    #[test]
+    fn ram_banks_wrap_by_ram_size() {
+        let mut cartridge = Cartridge::build(test_rom(0x13, 0x01, 0x02)).unwrap();
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0xA000, 0x42);
+
+        cartridge.write(0x4000, 0x03);
+        assert_eq!(cartridge.read(0xA000), 0x42);
+    }
 }
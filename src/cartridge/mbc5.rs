@@ -0,0 +1,175 @@
+use crate::cartridge::core::Core;
+use crate::cartridge::{
+    CartridgeEvent, MemoryBankController, MASK_MSB, MAX_PENDING_EVENTS, RAM_ADDRESS, RAM_BANK_SIZE,
+    ROM_BANK_SIZE,
+};
+
+const RUMBLE_MOTOR: u8 = 0b0000_1000;
+
+pub struct Mbc5 {
+    // The ROM bank number is 9 bits wide, unlike the u8 in the core
+    rom_bank: u16,
+    // Rumble cartridges wire bit 3 of the RAM bank register to the motor
+    has_rumble: bool,
+    rumble: bool,
+    events: Vec<CartridgeEvent>,
+}
+
+impl Mbc5 {
+    pub fn new(has_rumble: bool) -> Self {
+        Self {
+            rom_bank: 1,
+            has_rumble,
+            rumble: false,
+            events: Vec::new(),
+        }
+    }
+
+    fn set_rumble(&mut self, rumble: bool) {
+        if self.rumble == rumble {
+            return;
+        }
+
+        self.rumble = rumble;
+
+        if self.events.len() < MAX_PENDING_EVENTS {
+            self.events.push(CartridgeEvent::Rumble(rumble));
+        }
+    }
+}
+
+impl MemoryBankController for Mbc5 {
+    fn read_rom(&self, core: &Core, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x3FFF
+            // Bank 00 (read-only)
+            0x0..=0x3 => core.rom_data[address as usize],
+            // 0x4000 - 0x7FFF
+            // Bank 000-1FF (read-only), bank 0 is selectable as well
+            0x4..=0x7 => {
+                let bank_count = (core.rom_data.len() / ROM_BANK_SIZE).max(1);
+                let offset = ROM_BANK_SIZE * (self.rom_bank as usize % bank_count);
+                core.rom_data[(address as usize - ROM_BANK_SIZE) + offset]
+            }
+            _ => panic!("Address unknown: 0x{:#X}", address),
+        }
+    }
+
+    fn write_rom(&mut self, core: &mut Core, address: u16, value: u8) {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x1FFF
+            // RAM enable (write-only), only 0x0A enables
+            0x0 | 0x1 => {
+                core.ram_enabled = value == 0x0A;
+            }
+            // 0x2000 - 0x2FFF
+            // Lower 8 bits of the ROM bank number (write-only)
+            0x2 => {
+                self.rom_bank = (self.rom_bank & 0x0100) | value as u16;
+            }
+            // 0x3000 - 0x3FFF
+            // 9th bit of the ROM bank number (write-only)
+            0x3 => {
+                self.rom_bank = (self.rom_bank & 0x00FF) | ((value as u16 & 0b1) << 8);
+            }
+            // 0x4000 - 0x5FFF
+            // RAM bank number 00-0F (write-only)
+            0x4 | 0x5 => {
+                if self.has_rumble {
+                    core.ram_bank = value & 0b0000_0111;
+                    self.set_rumble(value & RUMBLE_MOTOR != 0);
+                } else {
+                    core.ram_bank = value & 0b0000_1111;
+                }
+            }
+            // 0x6000 - 0x7FFF
+            // Unused
+            0x6 | 0x7 => {}
+            _ => println!(
+                "Writing to unknown Cartridge ROM location 0x{:04x}",
+                address
+            ),
+        }
+    }
+
+    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) -> bool {
+        if !core.ram_enabled {
+            return false;
+        }
+
+        let ram_bank = core.ram_bank;
+        if let Some(ref mut ram_data) = core.ram_data {
+            let index = ram_index(ram_bank, ram_data.len(), address);
+            ram_data[index] = value;
+            return true;
+        }
+
+        false
+    }
+
+    fn read_ram(&self, core: &Core, address: u16) -> u8 {
+        if !core.ram_enabled {
+            return 0xFF;
+        }
+
+        if let Some(ref ram_data) = core.ram_data {
+            return ram_data[ram_index(core.ram_bank, ram_data.len(), address)];
+        }
+
+        0xFF
+    }
+
+    fn drain_events(&mut self) -> Vec<CartridgeEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+fn ram_index(ram_bank: u8, ram_size: usize, address: u16) -> usize {
+    // Banks beyond the RAM size wrap around
+    let offset = RAM_BANK_SIZE * ram_bank as usize + (address as usize - RAM_ADDRESS);
+    offset % ram_size
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cartridge::{test_rom, Cartridge, CartridgeEvent, ROM_BANK_SIZE};
+
+    #[test]
+    fn ninth_bank_bit_is_written_separately() {
+        let mut rom_data = test_rom(0x19, 0x08, 0x00);
+        rom_data[0x005 * ROM_BANK_SIZE] = 0x05;
+        rom_data[0x105 * ROM_BANK_SIZE] = 0x15;
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x05);
+        assert_eq!(cartridge.read(0x4000), 0x05);
+
+        cartridge.write(0x3000, 0x01);
+        assert_eq!(cartridge.read(0x4000), 0x15);
+    }
+
+    #[test]
+    fn bank_0_can_be_mapped_high() {
+        // Unlike on the other controllers, 0 isn't translated to 1
+        let mut rom_data = test_rom(0x19, 0x01, 0x00);
+        rom_data[0x0000] = 0xB0;
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x00);
+        assert_eq!(cartridge.read(0x4000), 0xB0);
+    }
+
+    #[test]
+    fn rumble_motor_is_reported_on_change() {
+        let mut cartridge = Cartridge::build(test_rom(0x1C, 0x01, 0x03)).unwrap();
+
+        cartridge.write(0x4000, 0x08);
+        cartridge.write(0x4000, 0x09);
+        cartridge.write(0x4000, 0x01);
+
+        assert_eq!(
+            cartridge.drain_events(),
+            [CartridgeEvent::Rumble(true), CartridgeEvent::Rumble(false)]
+        );
+    }
+}
@@ -1,47 +1,158 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
 mod core;
+mod header;
 mod mbc1;
+mod mbc2;
 mod mbc3;
+mod mbc5;
+mod no_mbc;
+mod rtc;
 
 use crate::cartridge::core::Core;
+pub use crate::cartridge::header::{CartridgeHeader, CgbSupport, HeaderError};
 use crate::cartridge::mbc1::Mbc1;
+use crate::cartridge::mbc2::Mbc2;
 use crate::cartridge::mbc3::Mbc3;
+use crate::cartridge::mbc5::Mbc5;
+use crate::cartridge::no_mbc::NoMbc;
+use crate::cartridge::rtc::{Rtc, RTC_TRAILER_SIZE_LEGACY};
+use crate::machine::CPU_CLOCK_SPEED;
 
 const ROM_BANK_SIZE: usize = 16 * 1024;
 const RAM_BANK_SIZE: usize = 8 * 1024;
 
 const RAM_ADDRESS: usize = 0xA000;
-const CARTRIDGE_TYPE_ADDRESS: usize = 0x147;
-
-// const ROM_SIZE_ADDRESS: usize = 0x0148;
-const RAM_SIZE_ADDRESS: usize = 0x149;
 
 const MASK_MSB: u16 = 0xF000;
 
-const GAME_TYPE: u16 = 0x0143;
+// Dirty save RAM is written back once per emulated second
+const FLUSH_INTERVAL: u32 = CPU_CLOCK_SPEED;
+
+// Events are dropped if the host never drains them
+const MAX_PENDING_EVENTS: usize = 256;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CartridgeEvent {
+    // The rumble motor was switched on or off
+    Rumble(bool),
+}
 
 pub trait MemoryBankController {
     fn read_rom(&self, core: &Core, address: u16) -> u8;
     fn write_rom(&mut self, core: &mut Core, address: u16, value: u8);
     fn read_ram(&self, core: &Core, address: u16) -> u8;
-    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8);
+    // Returns whether RAM or the RTC was written, which
+    // marks battery-backed state for the next save flush
+    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) -> bool;
+
+    fn tick(&mut self, _cycles: u8) {}
+
+    fn rtc(&self) -> Option<&Rtc> {
+        None
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        None
+    }
+
+    fn drain_events(&mut self) -> Vec<CartridgeEvent> {
+        Vec::new()
+    }
 }
 
 pub struct Cartridge {
+    pub header: CartridgeHeader,
     pub core: Core,
     pub mbc: Box<dyn MemoryBankController>,
+    battery: bool,
+    save_path: Option<PathBuf>,
+    // RAM was written since the last flush to the save file
+    dirty: bool,
+    flush_cycles: u32,
 }
 
 impl Cartridge {
-    pub fn build(rom_data: Vec<u8>) -> Self {
-        let core = Core::new(&rom_data);
+    pub fn build(rom_data: Vec<u8>) -> Result<Self, HeaderError> {
+        // Only a header that can't be used to set up the cartridge is an
+        // error, checking for a bad dump with validate is up to the caller
+
+        let header = CartridgeHeader::parse(&rom_data)?;
+
+        let mbc: Box<dyn MemoryBankController> = match header.cartridge_type {
+            0x00 | 0x08 | 0x09 => Box::new(NoMbc::new()),
+            0x01..=0x03 => Box::new(Mbc1::new(&rom_data)),
+            0x05 | 0x06 => Box::new(Mbc2::new()),
+            0x0F | 0x10 => Box::new(Mbc3::new(true)),
+            0x11..=0x13 => Box::new(Mbc3::new(false)),
+            0x19..=0x1B => Box::new(Mbc5::new(false)),
+            0x1C..=0x1E => Box::new(Mbc5::new(true)),
+            cartridge_type => return Err(HeaderError::UnsupportedCartridgeType(cartridge_type)),
+        };
+
+        let core = Core::new(rom_data, &header);
+        let battery = has_battery(header.cartridge_type);
+
+        Ok(Self {
+            header,
+            core,
+            mbc,
+            battery,
+            save_path: None,
+            dirty: false,
+            flush_cycles: 0,
+        })
+    }
+
+    pub fn load_save(&mut self, path: &Path) -> io::Result<()> {
+        // Battery-backed RAM is restored from the save file if it
+        // exists, which is also where it will be flushed to later
+
+        if !self.battery {
+            return Ok(());
+        }
 
-        let mbc: Box<dyn MemoryBankController> = match rom_data[CARTRIDGE_TYPE_ADDRESS] {
-            0x01..=0x03 => Box::new(Mbc1::new()),
-            0x0F..=0x13 => Box::new(Mbc3::new()),
-            _ => panic!("Error: Cartridge type not supported"),
+        self.save_path = Some(path.to_path_buf());
+
+        let data = match fs::read(path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error),
         };
 
-        Self { core, mbc }
+        let ram_size = self.core.ram_data.as_ref().map_or(0, Vec::len);
+
+        if let Some(ref mut ram_data) = self.core.ram_data {
+            let size = ram_size.min(data.len());
+            ram_data[..size].copy_from_slice(&data[..size]);
+        }
+
+        if let Some(rtc) = self.mbc.rtc_mut() {
+            if data.len() >= ram_size + RTC_TRAILER_SIZE_LEGACY {
+                rtc.load(&data[ram_size..]);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn flush_save(&mut self) -> io::Result<()> {
+        let Some(ref path) = self.save_path else {
+            return Ok(());
+        };
+
+        let mut data = self.core.ram_data.clone().unwrap_or_default();
+
+        if let Some(rtc) = self.mbc.rtc() {
+            data.extend_from_slice(&rtc.save());
+        }
+
+        fs::write(path, data)?;
+        self.dirty = false;
+
+        Ok(())
     }
 
     pub fn read(&self, addr: u16) -> u8 {
@@ -59,8 +170,142 @@ impl Cartridge {
     pub fn write(&mut self, addr: u16, value: u8) {
         match (addr & MASK_MSB) >> 12 {
             0x0..=0x7 => self.mbc.write_rom(&mut self.core, addr, value),
-            0xA | 0xB => self.mbc.write_ram(&mut self.core, addr, value),
+            0xA | 0xB => {
+                if self.mbc.write_ram(&mut self.core, addr, value) {
+                    self.dirty = true;
+                }
+            }
             _ => println!("Writing to unknown Cartridge address 0x{:#X}", addr),
         }
     }
+
+    pub fn tick(&mut self, cycles: u8) {
+        self.mbc.tick(cycles);
+
+        self.flush_cycles += cycles as u32;
+        if self.flush_cycles >= FLUSH_INTERVAL {
+            self.flush_cycles -= FLUSH_INTERVAL;
+
+            if self.dirty {
+                if let Err(error) = self.flush_save() {
+                    eprintln!("Error: Can't write save file: {}", error);
+                }
+            }
+        }
+    }
+
+    pub fn drain_events(&mut self) -> Vec<CartridgeEvent> {
+        self.mbc.drain_events()
+    }
+}
+
+impl Drop for Cartridge {
+    fn drop(&mut self) {
+        // The save is always flushed on exit, so the RTC
+        // trailer holds the time the emulator was closed
+        if let Err(error) = self.flush_save() {
+            eprintln!("Error: Can't write save file: {}", error);
+        }
+    }
+}
+
+fn has_battery(cartridge_type: u8) -> bool {
+    matches!(
+        cartridge_type,
+        0x03 | 0x06 | 0x09 | 0x0D | 0x0F | 0x10 | 0x13 | 0x1B | 0x1E | 0x22 | 0xFF
+    )
+}
+
+#[cfg(test)]
+fn test_rom(cartridge_type: u8, rom_size_code: u8, ram_size_code: u8) -> Vec<u8> {
+    // A blank ROM of the size given in the header, tests write
+    // the bytes they rely on, like bank markers, themselves
+
+    let mut rom_data = vec![0; (32 * 1024) << rom_size_code];
+    rom_data[0x0147] = cartridge_type;
+    rom_data[0x0148] = rom_size_code;
+    rom_data[0x0149] = ram_size_code;
+
+    rom_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("gemboi-{}-{}.sav", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        path
+    }
+
+    #[test]
+    fn save_round_trips_ram() {
+        let path = save_path("round-trip");
+
+        let mut cartridge = Cartridge::build(test_rom(0x09, 0x00, 0x02)).unwrap();
+        cartridge.load_save(&path).unwrap();
+        cartridge.write(0xA123, 0x42);
+        drop(cartridge);
+
+        assert_eq!(fs::read(&path).unwrap().len(), RAM_BANK_SIZE);
+
+        let mut cartridge = Cartridge::build(test_rom(0x09, 0x00, 0x02)).unwrap();
+        cartridge.load_save(&path).unwrap();
+        assert_eq!(cartridge.read(0xA123), 0x42);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_without_battery_is_not_written() {
+        let path = save_path("no-battery");
+
+        let mut cartridge = Cartridge::build(test_rom(0x08, 0x00, 0x02)).unwrap();
+        cartridge.load_save(&path).unwrap();
+        cartridge.write(0xA000, 0x42);
+        drop(cartridge);
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn dirty_ram_is_flushed_periodically() {
+        let path = save_path("periodic");
+
+        let mut cartridge = Cartridge::build(test_rom(0x03, 0x01, 0x02)).unwrap();
+        cartridge.load_save(&path).unwrap();
+
+        // Writes while RAM is disabled don't mark the save dirty
+        cartridge.write(0xA000, 0x42);
+        for _ in 0..FLUSH_INTERVAL / 4 {
+            cartridge.tick(4);
+        }
+        assert!(!path.exists());
+
+        cartridge.write(0x0000, 0x0A);
+        cartridge.write(0xA000, 0x42);
+        for _ in 0..FLUSH_INTERVAL / 4 {
+            cartridge.tick(4);
+        }
+        assert_eq!(fs::read(&path).unwrap()[0], 0x42);
+
+        drop(cartridge);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_appends_rtc_trailer() {
+        let path = save_path("rtc");
+
+        let mut cartridge = Cartridge::build(test_rom(0x10, 0x01, 0x03)).unwrap();
+        cartridge.load_save(&path).unwrap();
+        drop(cartridge);
+
+        let data = fs::read(&path).unwrap();
+        assert_eq!(data.len(), 4 * RAM_BANK_SIZE + rtc::RTC_TRAILER_SIZE);
+
+        fs::remove_file(&path).unwrap();
+    }
 }
@@ -0,0 +1,77 @@
+use crate::cartridge::core::Core;
+use crate::cartridge::{MemoryBankController, MASK_MSB, RAM_ADDRESS};
+
+pub struct NoMbc {}
+
+impl NoMbc {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl MemoryBankController for NoMbc {
+    fn read_rom(&self, core: &Core, address: u16) -> u8 {
+        match (address & MASK_MSB) >> 12 {
+            // 0x0000 - 0x7FFF
+            // Both banks are mapped directly (read-only)
+            0x0..=0x7 => core.rom_data.get(address as usize).copied().unwrap_or(0xFF),
+            _ => panic!("Address unknown: 0x{:#X}", address),
+        }
+    }
+
+    fn write_rom(&mut self, _core: &mut Core, _address: u16, _value: u8) {
+        // There are no registers, so writes are ignored
+    }
+
+    fn write_ram(&mut self, core: &mut Core, address: u16, value: u8) -> bool {
+        // The optional RAM is always enabled as there is no enable register
+
+        if let Some(ref mut ram_data) = core.ram_data {
+            let index = address as usize - RAM_ADDRESS;
+
+            if let Some(byte) = ram_data.get_mut(index) {
+                *byte = value;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn read_ram(&self, core: &Core, address: u16) -> u8 {
+        if let Some(ref ram_data) = core.ram_data {
+            return ram_data
+                .get(address as usize - RAM_ADDRESS)
+                .copied()
+                .unwrap_or(0xFF);
+        }
+
+        0xFF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cartridge::{test_rom, Cartridge};
+
+    #[test]
+    fn rom_writes_are_ignored() {
+        let mut rom_data = test_rom(0x00, 0x00, 0x00);
+        rom_data[0x4000] = 0x01;
+        let mut cartridge = Cartridge::build(rom_data).unwrap();
+
+        cartridge.write(0x2000, 0x02);
+        cartridge.write(0x4000, 0x02);
+
+        assert_eq!(cartridge.read(0x4000), 0x01);
+        assert_eq!(cartridge.read(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn ram_is_always_enabled() {
+        let mut cartridge = Cartridge::build(test_rom(0x08, 0x00, 0x02)).unwrap();
+
+        cartridge.write(0xBFFF, 0x42);
+        assert_eq!(cartridge.read(0xBFFF), 0x42);
+    }
+}
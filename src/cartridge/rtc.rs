@@ -0,0 +1,303 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::machine::CPU_CLOCK_SPEED;
+
+pub const RTC_SECONDS: u8 = 0x08;
+pub const RTC_MINUTES: u8 = 0x09;
+pub const RTC_HOURS: u8 = 0x0A;
+pub const RTC_DAY_LOW: u8 = 0x0B;
+pub const RTC_DAY_HIGH: u8 = 0x0C;
+
+// Bit 0: bit 8 of the day counter, bit 6: halt, bit 7: day counter carry
+const DAY_HIGH_BIT: u8 = 0b0000_0001;
+const HALT: u8 = 0b0100_0000;
+const CARRY: u8 = 0b1000_0000;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// Save files of BGB and VBA append the live and latched registers as
+// 32-bit values, followed by a 64-bit (or older 32-bit) UNIX timestamp
+pub const RTC_TRAILER_SIZE: usize = 48;
+pub const RTC_TRAILER_SIZE_LEGACY: usize = 44;
+const DAY_COUNTER_MAX: u64 = 512;
+
+pub struct Rtc {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    days: u16,
+    halted: bool,
+    carry: bool,
+    // Snapshot of the registers the game reads, taken on latch
+    latched: [u8; 5],
+    // T-cycles since the last full second
+    cycles: u32,
+}
+
+impl Rtc {
+    pub fn new() -> Self {
+        Self {
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            days: 0,
+            halted: false,
+            carry: false,
+            latched: [0; 5],
+            cycles: 0,
+        }
+    }
+
+    pub fn read(&self, register: u8) -> u8 {
+        self.latched[(register - RTC_SECONDS) as usize]
+    }
+
+    pub fn write(&mut self, register: u8, value: u8) {
+        // Writes go to the live registers, the latched copy is unchanged
+
+        match register {
+            RTC_SECONDS => {
+                // Writing the seconds resets the sub-second counter
+                self.seconds = value & 0b0011_1111;
+                self.cycles = 0;
+            }
+            RTC_MINUTES => self.minutes = value & 0b0011_1111,
+            RTC_HOURS => self.hours = value & 0b0001_1111,
+            RTC_DAY_LOW => self.days = (self.days & 0x0100) | value as u16,
+            RTC_DAY_HIGH => {
+                self.days = (self.days & 0x00FF) | (((value & DAY_HIGH_BIT) as u16) << 8);
+                self.halted = value & HALT != 0;
+                self.carry = value & CARRY != 0;
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn latch(&mut self) {
+        self.latched = self.registers();
+    }
+
+    pub fn tick(&mut self, cycles: u8) {
+        // The clock runs off its own 32.768 kHz crystal, which
+        // is modelled as one second every CPU_CLOCK_SPEED T-cycles
+
+        if self.halted {
+            return;
+        }
+
+        self.cycles += cycles as u32;
+
+        if self.cycles >= CPU_CLOCK_SPEED {
+            self.cycles -= CPU_CLOCK_SPEED;
+            self.advance_second();
+        }
+    }
+
+    pub fn save(&self) -> Vec<u8> {
+        let mut trailer = Vec::with_capacity(RTC_TRAILER_SIZE);
+
+        for value in self.registers().iter().chain(self.latched.iter()) {
+            trailer.extend_from_slice(&(*value as u32).to_le_bytes());
+        }
+
+        trailer.extend_from_slice(&unix_time().to_le_bytes());
+        trailer
+    }
+
+    pub fn load(&mut self, trailer: &[u8]) {
+        // Restores the registers from a BGB/VBA trailer and
+        // catches up with the time that passed since saving
+
+        let value = |index: usize| trailer[index * 4];
+
+        self.seconds = value(0) & 0b0011_1111;
+        self.minutes = value(1) & 0b0011_1111;
+        self.hours = value(2) & 0b0001_1111;
+        self.write(RTC_DAY_LOW, value(3));
+        self.write(RTC_DAY_HIGH, value(4));
+
+        for (index, latched) in self.latched.iter_mut().enumerate() {
+            *latched = value(5 + index);
+        }
+
+        let timestamp = if trailer.len() >= RTC_TRAILER_SIZE {
+            u64::from_le_bytes(trailer[40..48].try_into().unwrap())
+        } else {
+            u32::from_le_bytes(trailer[40..44].try_into().unwrap()) as u64
+        };
+
+        self.catch_up(timestamp);
+    }
+
+    fn catch_up(&mut self, timestamp: u64) {
+        // Advances the clock by the wall-clock time that passed since
+        // the given UNIX timestamp, e.g. while the emulator was closed
+
+        if self.halted {
+            return;
+        }
+
+        self.advance_seconds(unix_time().saturating_sub(timestamp));
+    }
+
+    fn advance_seconds(&mut self, seconds: u64) {
+        // Registers written with out-of-range values count up to their
+        // bit limit and wrap without a carry, so those are stepped one
+        // second at a time until everything is in range again
+
+        let mut remaining = seconds;
+
+        while remaining > 0 && (self.seconds > 59 || self.minutes > 59 || self.hours > 23) {
+            self.advance_second();
+            remaining -= 1;
+        }
+
+        let total = remaining
+            + self.seconds as u64
+            + self.minutes as u64 * 60
+            + self.hours as u64 * 60 * 60
+            + self.days as u64 * SECONDS_PER_DAY;
+
+        let days = total / SECONDS_PER_DAY;
+        if days >= DAY_COUNTER_MAX {
+            self.carry = true;
+        }
+
+        self.seconds = (total % 60) as u8;
+        self.minutes = (total / 60 % 60) as u8;
+        self.hours = (total / (60 * 60) % 24) as u8;
+        self.days = (days % DAY_COUNTER_MAX) as u16;
+    }
+
+    fn advance_second(&mut self) {
+        self.seconds = (self.seconds + 1) & 0b0011_1111;
+        if self.seconds != 60 {
+            return;
+        }
+
+        self.seconds = 0;
+        self.minutes = (self.minutes + 1) & 0b0011_1111;
+        if self.minutes != 60 {
+            return;
+        }
+
+        self.minutes = 0;
+        self.hours = (self.hours + 1) & 0b0001_1111;
+        if self.hours != 24 {
+            return;
+        }
+
+        self.hours = 0;
+        self.days += 1;
+
+        if self.days as u64 == DAY_COUNTER_MAX {
+            self.days = 0;
+            self.carry = true;
+        }
+    }
+
+    fn registers(&self) -> [u8; 5] {
+        let day_high = ((self.days >> 8) as u8 & DAY_HIGH_BIT)
+            | if self.halted { HALT } else { 0 }
+            | if self.carry { CARRY } else { 0 };
+
+        [
+            self.seconds,
+            self.minutes,
+            self.hours,
+            self.days as u8,
+            day_high,
+        ]
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_return_latched_registers() {
+        let mut rtc = Rtc::new();
+        rtc.write(RTC_SECONDS, 30);
+        assert_eq!(rtc.read(RTC_SECONDS), 0);
+
+        rtc.latch();
+        rtc.write(RTC_SECONDS, 45);
+        assert_eq!(rtc.read(RTC_SECONDS), 30);
+    }
+
+    #[test]
+    fn ticks_once_per_emulated_second() {
+        let mut rtc = Rtc::new();
+
+        for _ in 0..CPU_CLOCK_SPEED / 4 - 1 {
+            rtc.tick(4);
+        }
+        rtc.latch();
+        assert_eq!(rtc.read(RTC_SECONDS), 0);
+
+        rtc.tick(4);
+        rtc.latch();
+        assert_eq!(rtc.read(RTC_SECONDS), 1);
+    }
+
+    #[test]
+    fn halt_stops_the_clock() {
+        let mut rtc = Rtc::new();
+        rtc.write(RTC_DAY_HIGH, HALT);
+
+        for _ in 0..CPU_CLOCK_SPEED / 4 {
+            rtc.tick(4);
+        }
+        rtc.latch();
+        assert_eq!(rtc.read(RTC_SECONDS), 0);
+    }
+
+    #[test]
+    fn day_counter_overflow_sets_carry() {
+        let mut rtc = Rtc::new();
+        rtc.write(RTC_DAY_LOW, 0xFF);
+        rtc.write(RTC_DAY_HIGH, DAY_HIGH_BIT);
+        rtc.write(RTC_HOURS, 23);
+        rtc.write(RTC_MINUTES, 59);
+        rtc.write(RTC_SECONDS, 59);
+
+        rtc.advance_seconds(1);
+        rtc.latch();
+
+        assert_eq!(rtc.read(RTC_DAY_LOW), 0);
+        assert_eq!(rtc.read(RTC_DAY_HIGH), CARRY);
+    }
+
+    #[test]
+    fn out_of_range_registers_wrap_without_carry() {
+        let mut rtc = Rtc::new();
+        rtc.write(RTC_SECONDS, 63);
+
+        rtc.advance_seconds(61);
+        rtc.latch();
+
+        assert_eq!(rtc.read(RTC_SECONDS), 0);
+        assert_eq!(rtc.read(RTC_MINUTES), 1);
+    }
+
+    #[test]
+    fn load_catches_up_with_wall_clock() {
+        let mut rtc = Rtc::new();
+        let mut trailer = rtc.save();
+        let timestamp = unix_time() - SECONDS_PER_DAY - 90;
+        trailer[40..48].copy_from_slice(&timestamp.to_le_bytes());
+
+        rtc.load(&trailer);
+        rtc.latch();
+
+        assert_eq!(rtc.read(RTC_MINUTES), 1);
+        assert_eq!(rtc.read(RTC_DAY_LOW), 1);
+    }
+}
@@ -0,0 +1,27 @@
+use crate::cpu::Cpu;
+use crate::instruction::Target;
+
+pub fn bit_b_r(cpu: &mut Cpu, bit: u8, target: Target) {
+    // Test bit of the target register, set the zero
+    // flag if the bit is not set. Carry is left untouched
+
+    let reg = cpu.registers.get_register_value(&target);
+    let zero = reg & (1 << bit) == 0;
+
+    cpu.registers.f.set_zero(zero);
+    cpu.registers.f.set_subtract(false);
+    cpu.registers.f.set_half_carry(true);
+}
+
+pub fn bit_b_hl(cpu: &mut Cpu, bit: u8) {
+    // Test bit of the byte pointed to by HL, set the zero
+    // flag if the bit is not set. Carry is left untouched
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+    let zero = data & (1 << bit) == 0;
+
+    cpu.registers.f.set_zero(zero);
+    cpu.registers.f.set_subtract(false);
+    cpu.registers.f.set_half_carry(true);
+}
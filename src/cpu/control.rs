@@ -1,4 +1,5 @@
-use crate::cpu::Cpu;
+use crate::cpu::{Cpu, State};
+use crate::timer::DIV;
 
 pub fn daa(cpu: &mut Cpu) {
     // Decimal Adjust Accumulator to get a correct
@@ -69,4 +70,52 @@ pub fn disable_interrupt(cpu: &mut Cpu) {
     // instruction if any
 
     cpu.interrupt_enabled = false;
+    cpu.interrupt_enable_pending = false;
+}
+
+pub fn enable_interrupt(cpu: &mut Cpu) {
+    // Schedules interrupt handling to be enabled by setting
+    // IME=1. The effect is delayed by one instruction
+
+    cpu.interrupt_enable_pending = true;
+}
+
+pub fn halt(cpu: &mut Cpu) {
+    // Enters CPU low-power mode until an interrupt is pending,
+    // i.e. until IE & IF is non-zero. If IME=0 and an interrupt
+    // is already pending, HALT is exited immediately and the
+    // following byte is read twice (HALT bug)
+
+    let pending = cpu.memory_bus.get_pending_interrupts();
+
+    if !cpu.interrupt_enabled && pending != 0 {
+        cpu.halt_bug = true;
+        return;
+    }
+
+    cpu.state = State::Halted;
+}
+
+pub fn stop(cpu: &mut Cpu) {
+    // Enters CPU very low power mode until a joypad input is
+    // requested. The opcode is followed by a padding byte which
+    // is skipped. Entering STOP resets the divider
+
+    cpu.program_counter.next();
+
+    // In CGB mode, an armed speed switch happens instead
+    if cpu.memory_bus.switch_speed() {
+        return;
+    }
+
+    cpu.memory_bus.write_byte(DIV, 0);
+    cpu.state = State::Stopped;
+}
+
+pub fn illegal(cpu: &mut Cpu, opcode: u8) {
+    // Opcodes without an instruction hard-lock the CPU. Only
+    // a reset brings the system back, interrupts are ignored.
+    // The opcode is kept for the frontend to report
+
+    cpu.state = State::Locked(opcode);
 }
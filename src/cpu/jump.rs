@@ -21,6 +21,7 @@ pub fn jp_c_nn(cpu: &mut Cpu, flag: Flag) {
 
     if flag {
         cpu.program_counter.set(nn);
+        cpu.branch_taken = true;
     }
 }
 
@@ -34,6 +35,7 @@ pub fn jp_nc_nn(cpu: &mut Cpu, flag: Flag) {
 
     if !flag {
         cpu.program_counter.set(nn);
+        cpu.branch_taken = true;
     }
 }
 
@@ -63,6 +65,7 @@ pub fn jr_c_e(cpu: &mut Cpu, flag: Flag) {
 
     if flag {
         cpu.program_counter.relative_jump(address);
+        cpu.branch_taken = true;
     }
 }
 
@@ -76,6 +79,7 @@ pub fn jr_nc_e(cpu: &mut Cpu, flag: Flag) {
 
     if !flag {
         cpu.program_counter.relative_jump(address);
+        cpu.branch_taken = true;
     }
 }
 
@@ -97,7 +101,8 @@ pub fn call_c_nn(cpu: &mut Cpu, flag: Flag) {
 
     if flag {
         cpu.push_stack(cpu.program_counter.get());
-        cpu.program_counter.set(address)
+        cpu.program_counter.set(address);
+        cpu.branch_taken = true;
     }
 }
 
@@ -110,7 +115,8 @@ pub fn call_nc_nn(cpu: &mut Cpu, flag: Flag) {
 
     if !flag {
         cpu.push_stack(cpu.program_counter.get());
-        cpu.program_counter.set(address)
+        cpu.program_counter.set(address);
+        cpu.branch_taken = true;
     }
 }
 
@@ -131,7 +137,8 @@ pub fn ret(cpu: &mut Cpu) {
 
 pub fn reti(cpu: &mut Cpu) {
     // Unconditional return from a function
-    // Also enables interrupts by setting IME=1
+    // Also enables interrupts by setting IME=1,
+    // without the delay of EI
 
     let address = cpu.pop_stack();
     cpu.program_counter.set(address);
@@ -147,6 +154,7 @@ pub fn ret_c(cpu: &mut Cpu, flag: Flag) {
     if flag {
         let address = cpu.pop_stack();
         cpu.program_counter.set(address);
+        cpu.branch_taken = true;
     }
 }
 
@@ -159,5 +167,6 @@ pub fn ret_nc(cpu: &mut Cpu, flag: Flag) {
     if !flag {
         let address = cpu.pop_stack();
         cpu.program_counter.set(address);
+        cpu.branch_taken = true;
     }
 }
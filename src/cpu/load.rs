@@ -171,6 +171,32 @@ pub fn ldh_a_n(cpu: &mut Cpu) {
     cpu.registers.set_a(value);
 }
 
+pub fn ldh_c_a(cpu: &mut Cpu) {
+    // Load to the address specified by the 8-bit C register, data
+    // from the 8-bit A register. The full 16-bit absolute address
+    // is obtained by setting the most significant byte to 0xFF and
+    // the least significant byte to the value of C, so the possible
+    // range is 0xFF00-0xFFFF
+
+    let address = 0xFF00 | cpu.registers.get_c() as u16;
+    let value = cpu.registers.get_a();
+
+    cpu.memory_bus.write_byte(address, value);
+}
+
+pub fn ldh_a_c(cpu: &mut Cpu) {
+    // Load to the 8-bit A register, data from the address specified
+    // by the 8-bit C register. The full 16-bit absolute address is
+    // obtained by setting the most significant byte to 0xFF and the
+    // least significant byte to the value of C, so the possible
+    // range is 0xFF00-0xFFFF
+
+    let address = 0xFF00 | cpu.registers.get_c() as u16;
+    let value = cpu.memory_bus.read_byte(address);
+
+    cpu.registers.set_a(value);
+}
+
 pub fn ld_sp_nn(cpu: &mut Cpu) {
     // loads the immediate 16-bit value into the stack pointer register
 
@@ -1,62 +1,217 @@
 mod arithmetic;
+mod bit;
 mod control;
 mod jump;
 mod load;
 mod program_counter;
 mod reset;
 mod rotate;
+mod set;
 mod shift;
 
 use std::fs::File;
-use std::io::{LineWriter, Write};
+use std::io::{self, LineWriter, Write};
 
+use crate::cartridge::HeaderError;
 use crate::cpu::program_counter::ProgramCounter;
 use crate::instruction::{Instruction, Mnemonic};
+use crate::interrupt::Interrupt;
 use crate::memory_bus::MemoryBus;
+use crate::model::Model;
 use crate::registers::Registers;
 
-const HEADER_CHECKSUM_ADDRESS: usize = 0x014D;
 const STACK_POINTER_START: u16 = 0xFFFE;
 
+const T_CYCLES_PER_M_CYCLE: u8 = 4;
+const INTERRUPT_M_CYCLES: u8 = 5;
+
+#[derive(PartialEq)]
+enum State {
+    Running,
+    Halted,
+    Stopped,
+    // Holds the illegal opcode which locked up the CPU
+    Locked(u8),
+}
+
 pub struct Cpu {
     memory_bus: MemoryBus,
     registers: Registers,
     program_counter: ProgramCounter,
     stack_pointer: u16,
     interrupt_enabled: bool,
+    interrupt_enable_pending: bool,
+    halt_bug: bool,
+    branch_taken: bool,
+    state: State,
+    trace: Option<LineWriter<File>>,
 }
 
 impl Cpu {
-    pub fn new(rom_data: Vec<u8>) -> Self {
-        // If the header checksum is 0x00, then the carry and
-        // half-carry flags are clear; otherwise, they are both set
+    pub fn new(
+        rom_data: Vec<u8>,
+        boot_rom: Option<Vec<u8>>,
+        model: Model,
+    ) -> Result<Self, HeaderError> {
+        let has_boot_rom = boot_rom.is_some();
+        let memory_bus = MemoryBus::new(rom_data, boot_rom, model)?;
+        let registers = Registers::new(model, memory_bus.cartridge_header());
 
-        let enable_flags = rom_data[HEADER_CHECKSUM_ADDRESS] != 0x00;
-
-        Self {
-            memory_bus: MemoryBus::new(rom_data),
-            registers: Registers::new(enable_flags),
+        let mut cpu = Self {
+            memory_bus,
+            registers,
             program_counter: ProgramCounter::new(),
             stack_pointer: STACK_POINTER_START,
             interrupt_enabled: false,
+            interrupt_enable_pending: false,
+            halt_bug: false,
+            branch_taken: false,
+            state: State::Running,
+            trace: None,
+        };
+
+        // With a boot ROM, execution starts at 0x0000 from a cleared
+        // register file and the boot code sets up everything itself
+        if has_boot_rom {
+            cpu.registers.set_af(0x0000);
+            cpu.registers.set_bc(0x0000);
+            cpu.registers.set_de(0x0000);
+            cpu.registers.set_hl(0x0000);
+            cpu.program_counter.set(0x0000);
+            cpu.stack_pointer = 0x0000;
         }
+
+        Ok(cpu)
     }
 
-    pub fn step(&mut self, file: &mut LineWriter<File>) {
-        //self.log(file);
+    pub fn step(&mut self) -> u8 {
+        // Returns the number of T-cycles consumed. While halted,
+        // stopped or locked up, time still passes one M-cycle at a time
+
+        let pending = self.memory_bus.get_pending_interrupts();
+        let halted = self.state == State::Halted;
+
+        if !self.wake_up(pending) {
+            return T_CYCLES_PER_M_CYCLE;
+        }
+
+        if self.interrupt_enabled && pending != 0 {
+            // Exiting HALT takes an additional M-cycle
+            let m_cycles = INTERRUPT_M_CYCLES + halted as u8;
+            self.service_interrupt(pending);
+
+            return m_cycles * T_CYCLES_PER_M_CYCLE;
+        }
+
+        // EI takes effect after the instruction following it,
+        // unless that instruction is DI
+        let enable_interrupt = self.interrupt_enable_pending;
+
+        // A trace which can't be written to is dropped
+        if let Some(mut file) = self.trace.take() {
+            match self.log(&mut file) {
+                Ok(()) => self.trace = Some(file),
+                Err(error) => eprintln!("Warning: Can't write trace: {}", error),
+            }
+        }
 
         //print!("PC: {:#X} | ", self.program_counter.get());
-        let byte = self.memory_bus.read_byte(self.program_counter.next());
+        let address = self.fetch_address();
+        let byte = self.memory_bus.read_byte(address);
         //print!("Opcode: {:#X} | ", byte);
         let instruction = Instruction::from_byte(byte);
         //println!(
         //    "Instruction: {:?} | new PC: {:#X}",
         //    instruction.mnemonic, self.program_counter.value
         //);
-        self.execute_instruction(instruction);
+        let m_cycles = self.execute_instruction(instruction);
+
+        if enable_interrupt && self.interrupt_enable_pending {
+            self.interrupt_enabled = true;
+            self.interrupt_enable_pending = false;
+        }
+
+        m_cycles * T_CYCLES_PER_M_CYCLE
+    }
+
+    pub fn memory_bus(&self) -> &MemoryBus {
+        &self.memory_bus
+    }
+
+    pub fn memory_bus_mut(&mut self) -> &mut MemoryBus {
+        &mut self.memory_bus
+    }
+
+    pub fn set_trace(&mut self, file: LineWriter<File>) {
+        self.trace = Some(file);
+    }
+
+    pub fn locked_opcode(&self) -> Option<u8> {
+        match self.state {
+            State::Locked(opcode) => Some(opcode),
+            _ => None,
+        }
+    }
+
+    fn wake_up(&mut self, pending: u8) -> bool {
+        match self.state {
+            State::Running => {}
+            // HALT is exited as soon as any enabled interrupt is
+            // pending, regardless of IME
+            State::Halted => {
+                if pending != 0 {
+                    self.state = State::Running;
+                }
+            }
+            // STOP is only exited by a joypad input
+            State::Stopped => {
+                if self.memory_bus.is_requested(Interrupt::Joypad) {
+                    self.state = State::Running;
+                }
+            }
+            State::Locked(_) => {}
+        }
+
+        self.state == State::Running
+    }
+
+    fn service_interrupt(&mut self, pending: u8) {
+        // Clears IME and the IF bit of the highest priority
+        // interrupt, then calls its vector
+
+        let interrupt = match Interrupt::highest_priority(pending) {
+            Some(interrupt) => interrupt,
+            None => return,
+        };
+
+        self.interrupt_enabled = false;
+        self.interrupt_enable_pending = false;
+        self.memory_bus.clear_interrupt(interrupt);
+
+        self.push_stack(self.program_counter.get());
+        self.program_counter.set(interrupt.vector());
+    }
+
+    fn fetch_address(&mut self) -> u16 {
+        // After the HALT bug, the byte following HALT is read
+        // twice because the program counter fails to increment
+
+        if self.halt_bug {
+            self.halt_bug = false;
+            return self.program_counter.get();
+        }
+
+        self.program_counter.next()
     }
 
-    pub fn execute_instruction(&mut self, instruction: Instruction) {
+    pub fn execute_instruction(&mut self, instruction: Instruction) -> u8 {
+        // Returns the number of M-cycles consumed, which
+        // depends on whether a conditional branch was taken
+
+        let m_cycles = instruction.m_cycles;
+        let branch_m_cycles = instruction.branch_m_cycles;
+        self.branch_taken = false;
+
         match instruction.mnemonic {
             Mnemonic::NOP => {}
             Mnemonic::DAA => control::daa(self),
@@ -126,6 +281,8 @@ impl Cpu {
             Mnemonic::LD_nn_a => load::ld_nn_a(self),
             Mnemonic::LDH_n_a => load::ldh_n_a(self),
             Mnemonic::LDH_a_n => load::ldh_a_n(self),
+            Mnemonic::LDH_c_a => load::ldh_c_a(self),
+            Mnemonic::LDH_a_c => load::ldh_a_c(self),
             Mnemonic::LD_sp_nn => load::ld_sp_nn(self),
             Mnemonic::LD_sp_hl => load::ld_sp_hl(self),
             Mnemonic::LD_nn_sp => load::ld_nn_sp(self),
@@ -135,6 +292,10 @@ impl Cpu {
             Mnemonic::JR_e => jump::jr_e(self),
             Mnemonic::PUSH_rr(target) => load::push_rr(self, target),
             Mnemonic::DisableInterrupt => control::disable_interrupt(self),
+            Mnemonic::EnableInterrupt => control::enable_interrupt(self),
+            Mnemonic::HALT => control::halt(self),
+            Mnemonic::STOP => control::stop(self),
+            Mnemonic::Illegal(opcode) => control::illegal(self, opcode),
             Mnemonic::RRCA => rotate::rrca(self),
             Mnemonic::RRA => rotate::rra(self),
             Mnemonic::RLCA => rotate::rlca(self),
@@ -143,12 +304,20 @@ impl Cpu {
             Mnemonic::RET_nc(flag) => jump::ret_nc(self, flag),
             Mnemonic::RET => jump::ret(self),
             Mnemonic::RETI => jump::reti(self),
-            Mnemonic::Prefix => self.prefix(),
-            _ => panic!("Unknown mnemonic."),
+            Mnemonic::Prefix => return self.prefix(),
+            _ => unreachable!("PREFIX mnemonic decoded as unprefixed instruction."),
+        }
+
+        if self.branch_taken {
+            branch_m_cycles
+        } else {
+            m_cycles
         }
     }
 
-    fn prefix(&mut self) {
+    fn prefix(&mut self) -> u8 {
+        // The M-cycles of PREFIX instructions include the 0xCB fetch
+
         //print!("PC: {:#X} | ", self.program_counter.get());
         let byte = self.memory_bus.read_byte(self.program_counter.next());
         //print!("Opcode: {:#X} | ", byte);
@@ -157,22 +326,39 @@ impl Cpu {
         //  "Instruction: {:?} | new PC: {:#X}",
         //  instruction.mnemonic, self.program_counter.value
         //);
-        self.execute_prefix(instruction);
+        self.execute_prefix(instruction)
     }
 
-    fn execute_prefix(&mut self, instruction: Instruction) {
+    fn execute_prefix(&mut self, instruction: Instruction) -> u8 {
+        let m_cycles = instruction.m_cycles;
+
         match instruction.mnemonic {
             Mnemonic::RLC_r(target) => rotate::rlc_r(self, target),
+            Mnemonic::RLC_hl => rotate::rlc_hl(self),
             Mnemonic::RRC_r(target) => rotate::rrc_r(self, target),
+            Mnemonic::RRC_hl => rotate::rrc_hl(self),
             Mnemonic::RL_r(target) => rotate::rl_r(self, target),
+            Mnemonic::RL_hl => rotate::rl_hl(self),
             Mnemonic::RR_r(target) => rotate::rr_r(self, target),
+            Mnemonic::RR_hl => rotate::rr_hl(self),
+            Mnemonic::BIT_b_r(value, target) => bit::bit_b_r(self, value, target),
+            Mnemonic::BIT_b_hl(value) => bit::bit_b_hl(self, value),
             Mnemonic::RES_b_r(value, target) => reset::res_b_r(self, value, target),
+            Mnemonic::RES_b_hl(value) => reset::res_b_hl(self, value),
+            Mnemonic::SET_b_r(value, target) => set::set_b_r(self, value, target),
+            Mnemonic::SET_b_hl(value) => set::set_b_hl(self, value),
             Mnemonic::SRL_r(target) => shift::srl_r(self, target),
+            Mnemonic::SRL_hl => shift::srl_hl(self),
             Mnemonic::SLA_r(target) => shift::sla_r(self, target),
+            Mnemonic::SLA_hl => shift::sla_hl(self),
             Mnemonic::SRA_r(target) => shift::sra_r(self, target),
+            Mnemonic::SRA_hl => shift::sra_hl(self),
             Mnemonic::SWAP_r(target) => shift::swap_r(self, target),
+            Mnemonic::SWAP_hl => shift::swap_hl(self),
             _ => panic!("Unknown PREFIX Mnemnoic."),
         }
+
+        m_cycles
     }
 
     // --- Util ---
@@ -204,7 +390,8 @@ impl Cpu {
         self.memory_bus.write_byte(self.stack_pointer, low_byte);
     }
 
-    fn log(&self, file: &mut LineWriter<File>) {
+    // Debug helper, writes Gameboy Doctor compatible trace lines
+    fn log(&self, file: &mut LineWriter<File>) -> io::Result<()> {
         // A: 01 F: B0 B: 00 C: 13 D: 00 E: D8 H: 01 L: 4D SP: FFFE PC: 00:0100 (00 C3 13 02)
         let a = self.registers.get_a();
         let f = self.registers.get_f();
@@ -217,11 +404,118 @@ impl Cpu {
         let sp = self.stack_pointer;
         let pc = self.program_counter.get();
         let mem_pc = self.memory_bus.read_byte(self.program_counter.get());
-        let mem_pc2 = self.memory_bus.read_byte(self.program_counter.get() + 1);
-        let mem_pc3 = self.memory_bus.read_byte(self.program_counter.get() + 2);
-        let mem_pc4 = self.memory_bus.read_byte(self.program_counter.get() + 3);
+        let mem_pc2 = self.memory_bus.read_byte(self.program_counter.get().wrapping_add(1));
+        let mem_pc3 = self.memory_bus.read_byte(self.program_counter.get().wrapping_add(2));
+        let mem_pc4 = self.memory_bus.read_byte(self.program_counter.get().wrapping_add(3));
 
         let output = format!("A: {:02X} F: {:02X} B: {:02X} C: {:02X} D: {:02X} E: {:02X} H: {:02X} L: {:02X} SP: {:04X} PC: 00:{:04X} ({:02X} {:02X} {:02X} {:02X})\n", a, f, b, c, d, e, h, l, sp, pc, mem_pc, mem_pc2, mem_pc3, mem_pc4);
-        file.write_all(output.as_bytes()).unwrap();
+        file.write_all(output.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom(program: &[u8]) -> Vec<u8> {
+        // Jumps to the program at 0x0150, which ends by
+        // jumping to itself with JR -2
+
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+        rom_data[0x0150..0x0150 + program.len()].copy_from_slice(program);
+
+        rom_data
+    }
+
+    fn run(rom_data: Vec<u8>) -> Cpu {
+        let mut cpu = Cpu::new(rom_data, None, Model::Dmg).unwrap();
+        for _ in 0..100 {
+            cpu.step();
+        }
+
+        cpu
+    }
+
+    #[test]
+    fn prefixed_operations_on_hl() {
+        let cpu = run(rom(&[
+            0x21, 0x00, 0xC0, // LD HL,0xC000
+            0x36, 0x0F, // LD (HL),0x0F
+            0xCB, 0x36, // SWAP (HL)
+            0xCB, 0xC6, // SET 0,(HL)
+            0xCB, 0xBE, // RES 7,(HL)
+            0xCB, 0x7E, // BIT 7,(HL)
+            0xF5, // PUSH AF
+            0xC1, // POP BC
+            0x18, 0xFE, // JR -2
+        ]));
+
+        assert_eq!(cpu.memory_bus.read_byte(0xC000), 0x71);
+        // Z and H set by BIT, N and C cleared
+        assert_eq!(cpu.registers.get_c(), 0xA0);
+    }
+
+    fn run_with_timer_handler(program: &[u8]) -> Cpu {
+        // The timer interrupt handler stores 0x01 at 0xFF81
+
+        let mut rom_data = rom(program);
+        rom_data[0x0050..0x0055].copy_from_slice(&[
+            0x3E, 0x01, // LD A,0x01
+            0xE0, 0x81, // LDH (0x81),A
+            0xD9, // RETI
+        ]);
+
+        run(rom_data)
+    }
+
+    #[test]
+    fn interrupt_is_dispatched_and_acknowledged() {
+        let cpu = run_with_timer_handler(&[
+            0x3E, 0x04, // LD A,0x04
+            0xE0, 0xFF, // LDH (IE),A
+            0xE0, 0x0F, // LDH (IF),A
+            0xFB, // EI
+            0x00, // NOP
+            0xF0, 0x0F, // LDH A,(IF)
+            0xE0, 0x82, // LDH (0x82),A
+            0x18, 0xFE, // JR -2
+        ]);
+
+        assert_eq!(cpu.memory_bus.read_byte(0xFF81), 0x01);
+        assert_eq!(cpu.memory_bus.read_byte(0xFF82) & 0x04, 0);
+    }
+
+    #[test]
+    fn di_right_after_ei_blocks_interrupts() {
+        let cpu = run_with_timer_handler(&[
+            0x3E, 0x04, // LD A,0x04
+            0xE0, 0xFF, // LDH (IE),A
+            0xE0, 0x0F, // LDH (IF),A
+            0xFB, // EI
+            0xF3, // DI
+            0x00, // NOP
+            0x18, 0xFE, // JR -2
+        ]);
+
+        assert_eq!(cpu.memory_bus.read_byte(0xFF81), 0x00);
+    }
+
+    #[test]
+    fn halt_bug_repeats_next_byte() {
+        // HALT with IME clear and an interrupt pending doesn't
+        // increment PC, so INC A is executed twice
+        let cpu = run(rom(&[
+            0xF3, // DI
+            0x3E, 0x04, // LD A,0x04
+            0xE0, 0xFF, // LDH (IE),A
+            0xE0, 0x0F, // LDH (IF),A
+            0xAF, // XOR A
+            0x76, // HALT
+            0x3C, // INC A
+            0x18, 0xFE, // JR -2
+        ]));
+
+        assert_eq!(cpu.registers.get_a(), 0x02);
     }
 }
@@ -11,3 +11,14 @@ pub fn res_b_r(cpu: &mut Cpu, bit: u8, target: Target) {
 
     set_reg(&mut cpu.registers, result);
 }
+
+pub fn res_b_hl(cpu: &mut Cpu, bit: u8) {
+    // clear bit of the byte pointed to by HL
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let result = data & !(1 << bit);
+
+    cpu.memory_bus.write_byte(hl, result);
+}
@@ -8,7 +8,7 @@ pub fn rrca(cpu: &mut Cpu) {
 
     let a = cpu.registers.get_a();
     let shifted_out = (a & 0x01) != 0;
-    let result = (a >> 1) | (a << 7);
+    let result = a.rotate_right(1);
 
     cpu.registers.set_a(result);
     cpu.registers.f.set_flags(false, false, false, shifted_out);
@@ -35,7 +35,7 @@ pub fn rlca(cpu: &mut Cpu) {
 
     let a = cpu.registers.get_a();
     let shifted_out = (a & 0b1000_0000) != 0;
-    let result = (a << 1) | (a >> 7);
+    let result = a.rotate_left(1);
 
     cpu.registers.set_a(result);
     cpu.registers.f.set_flags(false, false, false, shifted_out);
@@ -61,7 +61,7 @@ pub fn rlc_r(cpu: &mut Cpu, target: Target) {
     let set_r = cpu.registers.get_register_setter(&target);
 
     let shifted_out = (r & 0b1000_0000) != 0;
-    let result = (r << 1) | (r >> 7);
+    let result = r.rotate_left(1);
 
     set_r(&mut cpu.registers, result);
     cpu.registers
@@ -76,7 +76,7 @@ pub fn rrc_r(cpu: &mut Cpu, target: Target) {
     let set_r = cpu.registers.get_register_setter(&target);
 
     let shifted_out = (r & 0x01) != 0;
-    let result = (r >> 1) | (r << 7);
+    let result = r.rotate_right(1);
 
     set_r(&mut cpu.registers, result);
     cpu.registers
@@ -115,3 +115,65 @@ pub fn rr_r(cpu: &mut Cpu, target: Target) {
         .f
         .set_flags(result == 0, false, false, shifted_out);
 }
+
+pub fn rlc_hl(cpu: &mut Cpu) {
+    // Rotate the byte pointed to by HL left
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let shifted_out = (data & 0b1000_0000) != 0;
+    let result = data.rotate_left(1);
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers
+        .f
+        .set_flags(result == 0, false, false, shifted_out);
+}
+
+pub fn rrc_hl(cpu: &mut Cpu) {
+    // Rotate the byte pointed to by HL right
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let shifted_out = (data & 0x01) != 0;
+    let result = data.rotate_right(1);
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers
+        .f
+        .set_flags(result == 0, false, false, shifted_out);
+}
+
+pub fn rl_hl(cpu: &mut Cpu) {
+    // Rotate the byte pointed to by HL left through carry
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+    let carry: u8 = cpu.registers.f.get_carry().into();
+
+    let shifted_out = (data & 0b1000_0000) != 0;
+    let result = (data << 1) | carry;
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers
+        .f
+        .set_flags(result == 0, false, false, shifted_out);
+}
+
+pub fn rr_hl(cpu: &mut Cpu) {
+    // Rotate the byte pointed to by HL right through carry
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+    let carry: u8 = cpu.registers.f.get_carry().into();
+
+    let shifted_out = (data & 0x01) != 0;
+    let result = (data >> 1) | (carry << 7);
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers
+        .f
+        .set_flags(result == 0, false, false, shifted_out);
+}
@@ -0,0 +1,24 @@
+use crate::cpu::Cpu;
+use crate::instruction::Target;
+
+pub fn set_b_r(cpu: &mut Cpu, bit: u8, target: Target) {
+    // set bit of the target register
+
+    let reg = cpu.registers.get_register_value(&target);
+    let set_reg = cpu.registers.get_register_setter(&target);
+
+    let result = reg | (1 << bit);
+
+    set_reg(&mut cpu.registers, result);
+}
+
+pub fn set_b_hl(cpu: &mut Cpu, bit: u8) {
+    // set bit of the byte pointed to by HL
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let result = data | (1 << bit);
+
+    cpu.memory_bus.write_byte(hl, result);
+}
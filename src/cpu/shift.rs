@@ -48,8 +48,59 @@ pub fn swap_r(cpu: &mut Cpu, target: Target) {
     let r = cpu.registers.get_register_value(&target);
     let set_r = cpu.registers.get_register_setter(&target);
 
-    let result = (r >> 4) | (r << 4);
+    let result = r.rotate_left(4);
 
     set_r(&mut cpu.registers, result);
     cpu.registers.f.set_flags(result == 0, false, false, false);
 }
+
+pub fn srl_hl(cpu: &mut Cpu) {
+    // Shift Right Logically the byte pointed to by HL
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let shifted_out = (data & 0b0000_0001) != 0;
+    let result = data >> 1;
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers.f.set_flags(result == 0, false, false, shifted_out);
+}
+
+pub fn sla_hl(cpu: &mut Cpu) {
+    // Shift Left Arithmetically the byte pointed to by HL
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let shifted_out = (data & 0b1000_0000) != 0;
+    let result = data << 1;
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers.f.set_flags(result == 0, false, false, shifted_out);
+}
+
+pub fn sra_hl(cpu: &mut Cpu) {
+    // Shift Right Arithmetically the byte pointed to by HL
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let shifted_out = (data & 0x01) != 0;
+    let result = (data >> 1) | (data & 0b1000_0000);
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers.f.set_flags(result == 0, false, false, shifted_out);
+}
+
+pub fn swap_hl(cpu: &mut Cpu) {
+    // Swap the upper 4 bits in the byte pointed to by HL and the lower 4 ones
+
+    let hl = cpu.registers.get_hl();
+    let data = cpu.memory_bus.read_byte(hl);
+
+    let result = data.rotate_left(4);
+
+    cpu.memory_bus.write_byte(hl, result);
+    cpu.registers.f.set_flags(result == 0, false, false, false);
+}
@@ -0,0 +1,88 @@
+use crate::gpu::OAM_SIZE;
+
+pub const DMA: u16 = 0xFF46;
+
+pub struct Dma {
+    register: u8,
+    source: u16,
+    index: u16,
+    // The transfer starts one M-cycle after writing to the register
+    starting: bool,
+    active: bool,
+}
+
+impl Dma {
+    pub fn new() -> Self {
+        Self {
+            register: 0xFF,
+            source: 0,
+            index: 0,
+            starting: false,
+            active: false,
+        }
+    }
+
+    pub fn read_register(&self) -> u8 {
+        self.register
+    }
+
+    pub fn start(&mut self, value: u8) {
+        // The source address is the written value times 0x100,
+        // the destination is always OAM
+
+        self.register = value;
+        self.source = (value as u16) << 8;
+        self.index = 0;
+        self.starting = true;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn step(&mut self) -> Option<(u16, u16)> {
+        // Advances the transfer by one M-cycle and returns the source
+        // address and the OAM offset of the byte to be copied, if any
+
+        if self.starting {
+            self.starting = false;
+            self.active = true;
+            return None;
+        }
+
+        if !self.active {
+            return None;
+        }
+
+        let transfer = (self.source + self.index, self.index);
+
+        self.index += 1;
+        if self.index as usize == OAM_SIZE {
+            self.active = false;
+        }
+
+        Some(transfer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_oam_one_byte_per_m_cycle_after_a_delay() {
+        let mut dma = Dma::new();
+        dma.start(0xC1);
+
+        assert_eq!(dma.step(), None);
+        assert!(dma.is_active());
+
+        for offset in 0..OAM_SIZE as u16 {
+            assert_eq!(dma.step(), Some((0xC100 + offset, offset)));
+        }
+
+        assert!(!dma.is_active());
+        assert_eq!(dma.step(), None);
+        assert_eq!(dma.read_register(), 0xC1);
+    }
+}
@@ -1,23 +1,690 @@
+use crate::interrupt::Interrupt;
+use crate::model::Model;
+
 pub const VRAM_SIZE: usize = 8192;
+pub const OAM_SIZE: usize = 160;
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+
+pub const LCDC: u16 = 0xFF40;
+pub const STAT: u16 = 0xFF41;
+pub const SCY: u16 = 0xFF42;
+pub const SCX: u16 = 0xFF43;
+pub const LY: u16 = 0xFF44;
+pub const LYC: u16 = 0xFF45;
+pub const BGP: u16 = 0xFF47;
+pub const OBP0: u16 = 0xFF48;
+pub const OBP1: u16 = 0xFF49;
+pub const WY: u16 = 0xFF4A;
+pub const WX: u16 = 0xFF4B;
+pub const VBK: u16 = 0xFF4F;
+pub const BCPS: u16 = 0xFF68;
+pub const BCPD: u16 = 0xFF69;
+pub const OCPS: u16 = 0xFF6A;
+pub const OCPD: u16 = 0xFF6B;
+
+// LCDC bits
+const LCD_ENABLE: u8 = 0b1000_0000;
+const WINDOW_TILE_MAP: u8 = 0b0100_0000;
+const WINDOW_ENABLE: u8 = 0b0010_0000;
+const TILE_DATA: u8 = 0b0001_0000;
+const BG_TILE_MAP: u8 = 0b0000_1000;
+const OBJ_SIZE: u8 = 0b0000_0100;
+const OBJ_ENABLE: u8 = 0b0000_0010;
+const BG_WINDOW_ENABLE: u8 = 0b0000_0001;
+
+// STAT bits
+const LYC_INTERRUPT: u8 = 0b0100_0000;
+const OAM_SCAN_INTERRUPT: u8 = 0b0010_0000;
+const VBLANK_INTERRUPT: u8 = 0b0001_0000;
+const HBLANK_INTERRUPT: u8 = 0b0000_1000;
+const LYC_EQUALS_LY: u8 = 0b0000_0100;
+const STAT_UNUSED: u8 = 0b1000_0000;
+const STAT_WRITABLE: u8 = 0b0111_1000;
+
+// OAM attribute bits
+const OBJ_BG_PRIORITY: u8 = 0b1000_0000;
+const OBJ_Y_FLIP: u8 = 0b0100_0000;
+const OBJ_X_FLIP: u8 = 0b0010_0000;
+const OBJ_PALETTE: u8 = 0b0001_0000;
+
+// CGB attribute bits, shared by OAM and the BG map attributes in VRAM bank 1
+const BG_PRIORITY: u8 = 0b1000_0000;
+const BG_Y_FLIP: u8 = 0b0100_0000;
+const BG_X_FLIP: u8 = 0b0010_0000;
+const VRAM_BANK: u8 = 0b0000_1000;
+const CGB_PALETTE: u8 = 0b0000_0111;
+
+// BCPS/OCPS hold the palette RAM index and the auto-increment flag
+const PALETTE_RAM_SIZE: usize = 64;
+const PALETTE_INDEX: u8 = 0b0011_1111;
+const PALETTE_AUTO_INCREMENT: u8 = 0b1000_0000;
+
+// RGB555 colors for the 4 shades in DMG mode, from white to black
+const DMG_COLORS: [u16; 4] = [0x7FFF, 0x56B5, 0x294A, 0x0000];
+
+const OAM_SCAN_DOTS: u16 = 80;
+const DRAWING_DOTS: u16 = 172;
+const SCANLINE_DOTS: u16 = 456;
+
+const VBLANK_START: u8 = 144;
+const LAST_SCANLINE: u8 = 153;
+
+const MAX_SPRITES_PER_LINE: usize = 10;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Mode {
+    HBlank = 0,
+    VBlank = 1,
+    OamScan = 2,
+    Drawing = 3,
+}
+
+struct Sprite {
+    y: i16,
+    x: i16,
+    tile_index: u8,
+    attributes: u8,
+}
 
 pub struct Gpu {
-    tile_set: [[u8; 8]; 384],
-    video_ram: [u8; VRAM_SIZE],
+    // Two banks in CGB mode, the second one holds the BG map attributes
+    video_ram: [u8; VRAM_SIZE * 2],
+    vram_bank: u8,
+    oam: [u8; OAM_SIZE],
+    // RGB555 colors (bits 0-4 red, 5-9 green, 10-14 blue)
+    pub frame_buffer: Box<[u16]>,
+    // Outside of CGB mode, the shades 0-3 the frame buffer colors
+    // were mapped from, which the SGB maps to its own palettes
+    shade_buffer: Box<[u8]>,
+    pub frame_complete: bool,
+    // Set when a scanline enters HBlank, which drives HBlank DMA
+    pub hblank_started: bool,
+    cgb_mode: bool,
+    bg_palette_ram: [u8; PALETTE_RAM_SIZE],
+    obj_palette_ram: [u8; PALETTE_RAM_SIZE],
+    bcps: u8,
+    ocps: u8,
+    mode: Mode,
+    dots: u16,
+    lcdc: u8,
+    stat: u8,
+    scy: u8,
+    scx: u8,
+    ly: u8,
+    lyc: u8,
+    bgp: u8,
+    obp0: u8,
+    obp1: u8,
+    wy: u8,
+    wx: u8,
+    // The window keeps its own line counter, which is only
+    // incremented on scanlines where the window was drawn
+    window_line: u8,
+    window_triggered: bool,
+    stat_line: bool,
 }
 
 impl Gpu {
-    pub fn new() -> Self {
+    pub fn new(model: Model, cgb_mode: bool) -> Self {
+        // The boot ROM hands over during the last scanline, which
+        // the early DMG0 boot ROM reaches slightly later
+        let dots = match model {
+            Model::Dmg0 => 0,
+            _ => 400,
+        };
+
         Self {
-            tile_set: [[0; 8]; 384],
-            video_ram: [0; VRAM_SIZE],
+            video_ram: [0; VRAM_SIZE * 2],
+            vram_bank: 0,
+            oam: [0; OAM_SIZE],
+            frame_buffer: vec![DMG_COLORS[0]; SCREEN_WIDTH * SCREEN_HEIGHT].into_boxed_slice(),
+            shade_buffer: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT].into_boxed_slice(),
+            frame_complete: false,
+            hblank_started: false,
+            cgb_mode,
+            // Palettes are initialized to white by the CGB boot ROM
+            bg_palette_ram: [0xFF; PALETTE_RAM_SIZE],
+            obj_palette_ram: [0; PALETTE_RAM_SIZE],
+            bcps: 0,
+            ocps: 0,
+            mode: Mode::VBlank,
+            dots,
+            lcdc: 0x91,
+            stat: 0,
+            scy: 0,
+            scx: 0,
+            ly: LAST_SCANLINE,
+            lyc: 0,
+            bgp: 0xFC,
+            obp0: 0,
+            obp1: 0,
+            wy: 0,
+            wx: 0,
+            window_line: 0,
+            window_triggered: false,
+            stat_line: false,
         }
     }
 
+    pub fn set_cgb_mode(&mut self, cgb_mode: bool) {
+        // VRAM bank 0 stays mapped outside of CGB mode
+        self.cgb_mode = cgb_mode;
+        self.vram_bank = 0;
+    }
+
     pub fn read_byte(&self, address: u16) -> u8 {
-        self.video_ram[address as usize]
+        self.video_ram[self.vram_bank as usize * VRAM_SIZE + address as usize]
     }
 
     pub fn write_byte(&mut self, address: u16, value: u8) {
-        self.video_ram[address as usize] = value;
+        self.video_ram[self.vram_bank as usize * VRAM_SIZE + address as usize] = value;
+    }
+
+    pub fn read_oam(&self, address: u16) -> u8 {
+        self.oam[address as usize]
+    }
+
+    pub fn write_oam(&mut self, address: u16, value: u8) {
+        self.oam[address as usize] = value;
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            LCDC => self.lcdc,
+            STAT => self.read_stat(),
+            SCY => self.scy,
+            SCX => self.scx,
+            LY => self.ly_register(),
+            LYC => self.lyc,
+            BGP => self.bgp,
+            OBP0 => self.obp0,
+            OBP1 => self.obp1,
+            WY => self.wy,
+            WX => self.wx,
+            // The CGB registers read 0xFF outside of CGB mode
+            _ if !self.cgb_mode => 0xFF,
+            VBK => self.vram_bank | 0b1111_1110,
+            BCPS => self.bcps | 0b0100_0000,
+            BCPD => self.read_palette_data(&self.bg_palette_ram, self.bcps),
+            OCPS => self.ocps | 0b0100_0000,
+            OCPD => self.read_palette_data(&self.obj_palette_ram, self.ocps),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            LCDC => self.write_lcdc(value),
+            STAT => self.stat = value & STAT_WRITABLE,
+            SCY => self.scy = value,
+            SCX => self.scx = value,
+            // LY is read-only
+            LY => {}
+            LYC => self.lyc = value,
+            BGP => self.bgp = value,
+            OBP0 => self.obp0 = value,
+            OBP1 => self.obp1 = value,
+            WY => self.wy = value,
+            WX => self.wx = value,
+            _ if !self.cgb_mode => {}
+            VBK => self.vram_bank = value & 0b1,
+            BCPS => self.bcps = value & (PALETTE_AUTO_INCREMENT | PALETTE_INDEX),
+            BCPD => {
+                if self.is_palette_accessible() {
+                    self.bg_palette_ram[(self.bcps & PALETTE_INDEX) as usize] = value;
+                }
+                self.bcps = increment_palette_index(self.bcps);
+            }
+            OCPS => self.ocps = value & (PALETTE_AUTO_INCREMENT | PALETTE_INDEX),
+            OCPD => {
+                if self.is_palette_accessible() {
+                    self.obj_palette_ram[(self.ocps & PALETTE_INDEX) as usize] = value;
+                }
+                self.ocps = increment_palette_index(self.ocps);
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn is_palette_accessible(&self) -> bool {
+        // Palette RAM is locked while the PPU is drawing
+        self.lcdc & LCD_ENABLE == 0 || self.mode != Mode::Drawing
+    }
+
+    fn read_palette_data(&self, palette_ram: &[u8; PALETTE_RAM_SIZE], specification: u8) -> u8 {
+        if self.is_palette_accessible() {
+            palette_ram[(specification & PALETTE_INDEX) as usize]
+        } else {
+            0xFF
+        }
+    }
+
+    pub fn tick(&mut self, cycles: u8) -> u8 {
+        // Advances the PPU one M-cycle (4 dots) at a time and
+        // returns the mask of the requested interrupts
+
+        let mut interrupts = 0;
+
+        if self.lcdc & LCD_ENABLE == 0 {
+            return interrupts;
+        }
+
+        for _ in 0..cycles / 4 {
+            self.dots += 4;
+
+            match self.mode {
+                Mode::OamScan => {
+                    if self.dots >= OAM_SCAN_DOTS {
+                        self.mode = Mode::Drawing;
+                    }
+                }
+                Mode::Drawing => {
+                    if self.dots >= OAM_SCAN_DOTS + DRAWING_DOTS {
+                        self.render_scanline();
+                        self.mode = Mode::HBlank;
+                        self.hblank_started = true;
+                    }
+                }
+                Mode::HBlank => {
+                    if self.dots >= SCANLINE_DOTS {
+                        self.dots = 0;
+                        self.ly += 1;
+
+                        if self.ly == VBLANK_START {
+                            self.mode = Mode::VBlank;
+                            self.frame_complete = true;
+                            interrupts |= Interrupt::VBlank.mask();
+                        } else {
+                            self.start_scanline();
+                        }
+                    }
+                }
+                Mode::VBlank => {
+                    if self.dots >= SCANLINE_DOTS {
+                        self.dots = 0;
+
+                        if self.ly == LAST_SCANLINE {
+                            self.ly = 0;
+                            self.window_line = 0;
+                            self.window_triggered = false;
+                            self.start_scanline();
+                        } else {
+                            self.ly += 1;
+                        }
+                    }
+                }
+            }
+
+            if self.update_stat_line() {
+                interrupts |= Interrupt::LcdStat.mask();
+            }
+        }
+
+        interrupts
+    }
+
+    fn start_scanline(&mut self) {
+        // The window is enabled for the rest of the frame once
+        // WY matched LY at the start of a scanline
+
+        if self.ly == self.wy {
+            self.window_triggered = true;
+        }
+
+        self.mode = Mode::OamScan;
+    }
+
+    fn read_stat(&self) -> u8 {
+        let coincidence = if self.ly_register() == self.lyc {
+            LYC_EQUALS_LY
+        } else {
+            0
+        };
+        let mode = if self.lcdc & LCD_ENABLE != 0 {
+            self.mode as u8
+        } else {
+            0
+        };
+
+        STAT_UNUSED | self.stat | coincidence | mode
+    }
+
+    fn write_lcdc(&mut self, value: u8) {
+        let was_enabled = self.lcdc & LCD_ENABLE != 0;
+        self.lcdc = value;
+
+        // Turning the LCD off resets LY and the mode, the screen
+        // is blank until it is turned on again
+        if was_enabled && value & LCD_ENABLE == 0 {
+            self.ly = 0;
+            self.dots = 0;
+            self.mode = Mode::HBlank;
+            self.stat_line = false;
+            self.frame_buffer.fill(DMG_COLORS[0]);
+            self.shade_buffer.fill(0);
+        } else if !was_enabled && value & LCD_ENABLE != 0 {
+            self.window_line = 0;
+            self.window_triggered = false;
+            self.start_scanline();
+        }
+    }
+
+    fn ly_register(&self) -> u8 {
+        // On the last scanline, LY already reads 0 after the first M-cycle
+
+        if self.ly == LAST_SCANLINE && self.dots >= 4 {
+            0
+        } else {
+            self.ly
+        }
+    }
+
+    fn update_stat_line(&mut self) -> bool {
+        // The STAT interrupt is requested on the rising edge of the
+        // OR of all enabled sources, so overlapping sources block
+        // each other (STAT blocking)
+
+        let line = (self.stat & LYC_INTERRUPT != 0 && self.ly_register() == self.lyc)
+            || (self.stat & OAM_SCAN_INTERRUPT != 0 && self.mode == Mode::OamScan)
+            || (self.stat & VBLANK_INTERRUPT != 0 && self.mode == Mode::VBlank)
+            || (self.stat & HBLANK_INTERRUPT != 0 && self.mode == Mode::HBlank);
+
+        let rising_edge = line && !self.stat_line;
+        self.stat_line = line;
+
+        rising_edge
+    }
+
+    // --- Rendering ---
+    fn render_scanline(&mut self) {
+        // Color indices and BG priority attributes before palette
+        // mapping, needed to resolve the priority of sprites
+        let mut bg_pixels = [(0, false); SCREEN_WIDTH];
+
+        // In CGB mode, LCDC bit 0 only removes the priority
+        // of the background instead of disabling it
+        if self.cgb_mode || self.lcdc & BG_WINDOW_ENABLE != 0 {
+            self.render_background(&mut bg_pixels);
+
+            if self.lcdc & WINDOW_ENABLE != 0 {
+                self.render_window(&mut bg_pixels);
+            }
+        } else {
+            let offset = self.ly as usize * SCREEN_WIDTH;
+            self.frame_buffer[offset..offset + SCREEN_WIDTH].fill(DMG_COLORS[0]);
+            self.shade_buffer[offset..offset + SCREEN_WIDTH].fill(0);
+        }
+
+        if self.lcdc & OBJ_ENABLE != 0 {
+            self.render_sprites(&bg_pixels);
+        }
     }
+
+    fn render_background(&mut self, bg_pixels: &mut [(u8, bool); SCREEN_WIDTH]) {
+        let tile_map = if self.lcdc & BG_TILE_MAP != 0 {
+            0x1C00
+        } else {
+            0x1800
+        };
+        let y = self.ly.wrapping_add(self.scy);
+
+        for (screen_x, bg_pixel) in bg_pixels.iter_mut().enumerate() {
+            let x = (screen_x as u8).wrapping_add(self.scx);
+            let (color, attributes) = self.tile_map_pixel(tile_map, x, y);
+
+            *bg_pixel = (color, attributes & BG_PRIORITY != 0);
+            let (shade, rgb) = self.bg_color(attributes, color);
+            self.set_pixel(screen_x, shade, rgb);
+        }
+    }
+
+    fn render_window(&mut self, bg_pixels: &mut [(u8, bool); SCREEN_WIDTH]) {
+        if !self.window_triggered || self.wx > 166 {
+            return;
+        }
+
+        let tile_map = if self.lcdc & WINDOW_TILE_MAP != 0 {
+            0x1C00
+        } else {
+            0x1800
+        };
+        let start = self.wx.saturating_sub(7) as usize;
+
+        for (screen_x, bg_pixel) in bg_pixels.iter_mut().enumerate().skip(start) {
+            let x = (screen_x + 7 - self.wx as usize) as u8;
+            let (color, attributes) = self.tile_map_pixel(tile_map, x, self.window_line);
+
+            *bg_pixel = (color, attributes & BG_PRIORITY != 0);
+            let (shade, rgb) = self.bg_color(attributes, color);
+            self.set_pixel(screen_x, shade, rgb);
+        }
+
+        self.window_line += 1;
+    }
+
+    fn render_sprites(&mut self, bg_pixels: &[(u8, bool); SCREEN_WIDTH]) {
+        let height = if self.lcdc & OBJ_SIZE != 0 { 16 } else { 8 };
+        let mut sprites = self.scan_oam(height);
+
+        // On DMG, the sprite with the smaller X coordinate has
+        // priority, ties are resolved by the position in OAM.
+        // In CGB mode, only the position in OAM matters
+        if !self.cgb_mode {
+            sprites.sort_by_key(|sprite| sprite.x);
+        }
+
+        // Without the BG master priority, sprites are always on top
+        let bg_has_priority = !self.cgb_mode || self.lcdc & BG_WINDOW_ENABLE != 0;
+
+        let mut drawn = [false; SCREEN_WIDTH];
+
+        for sprite in sprites {
+            let mut line = (self.ly as i16 - sprite.y) as u8;
+            if sprite.attributes & OBJ_Y_FLIP != 0 {
+                line = height - 1 - line;
+            }
+
+            // In 8x16 mode, bit 0 of the tile index is ignored
+            let tile_index = if height == 16 {
+                (sprite.tile_index & 0xFE) + line / 8
+            } else {
+                sprite.tile_index
+            };
+
+            let bank_offset = if self.cgb_mode && sprite.attributes & VRAM_BANK != 0 {
+                VRAM_SIZE
+            } else {
+                0
+            };
+
+            for pixel in 0..8 {
+                let screen_x = sprite.x + pixel as i16;
+                if !(0..SCREEN_WIDTH as i16).contains(&screen_x) {
+                    continue;
+                }
+
+                let screen_x = screen_x as usize;
+                if drawn[screen_x] {
+                    continue;
+                }
+
+                let x = if sprite.attributes & OBJ_X_FLIP != 0 {
+                    7 - pixel
+                } else {
+                    pixel
+                };
+                let color = self.tile_pixel(bank_offset + tile_index as usize * 16, x, line % 8);
+
+                // Color 0 is transparent
+                if color == 0 {
+                    continue;
+                }
+
+                drawn[screen_x] = true;
+
+                let (bg_color, bg_priority) = bg_pixels[screen_x];
+                let behind_bg = sprite.attributes & OBJ_BG_PRIORITY != 0 || bg_priority;
+                if bg_has_priority && behind_bg && bg_color != 0 {
+                    continue;
+                }
+
+                let (shade, rgb) = self.obj_color(sprite.attributes, color);
+                self.set_pixel(screen_x, shade, rgb);
+            }
+        }
+    }
+
+    fn scan_oam(&self, height: u8) -> Vec<Sprite> {
+        // Selects the first 10 sprites in OAM which overlap the current scanline
+
+        self.oam
+            .chunks_exact(4)
+            .map(|entry| Sprite {
+                y: entry[0] as i16 - 16,
+                x: entry[1] as i16 - 8,
+                tile_index: entry[2],
+                attributes: entry[3],
+            })
+            .filter(|sprite| {
+                let ly = self.ly as i16;
+                ly >= sprite.y && ly < sprite.y + height as i16
+            })
+            .take(MAX_SPRITES_PER_LINE)
+            .collect()
+    }
+
+    fn tile_map_pixel(&self, tile_map: usize, x: u8, y: u8) -> (u8, u8) {
+        // Returns the color index and the attributes of the tile,
+        // which are stored at the same position in VRAM bank 1
+
+        let map_address = tile_map + (y as usize / 8) * 32 + x as usize / 8;
+        let tile_index = self.video_ram[map_address];
+        let attributes = if self.cgb_mode {
+            self.video_ram[VRAM_SIZE + map_address]
+        } else {
+            0
+        };
+
+        // 0x8000 addressing uses unsigned tile indices, 0x8800
+        // addressing uses signed tile indices based at 0x9000
+        let mut tile_address = if self.lcdc & TILE_DATA != 0 {
+            tile_index as usize * 16
+        } else {
+            (0x1000 + (tile_index as i8 as i32) * 16) as usize
+        };
+
+        if attributes & VRAM_BANK != 0 {
+            tile_address += VRAM_SIZE;
+        }
+
+        let tile_x = if attributes & BG_X_FLIP != 0 {
+            7 - x % 8
+        } else {
+            x % 8
+        };
+        let tile_y = if attributes & BG_Y_FLIP != 0 {
+            7 - y % 8
+        } else {
+            y % 8
+        };
+
+        (self.tile_pixel(tile_address, tile_x, tile_y), attributes)
+    }
+
+    fn tile_pixel(&self, tile_address: usize, x: u8, y: u8) -> u8 {
+        // Each tile row takes two bytes, the first one holding the
+        // low bits of the color indices, the second one the high bits
+
+        let low = self.video_ram[tile_address + y as usize * 2];
+        let high = self.video_ram[tile_address + y as usize * 2 + 1];
+        let bit = 7 - x;
+
+        (((high >> bit) & 0b1) << 1) | ((low >> bit) & 0b1)
+    }
+
+    fn bg_color(&self, attributes: u8, color: u8) -> (u8, u16) {
+        // Returns the shade along with the color, the shade
+        // is only meaningful outside of CGB mode
+
+        if self.cgb_mode {
+            let rgb = palette_color(&self.bg_palette_ram, attributes & CGB_PALETTE, color);
+            return (color, rgb);
+        }
+
+        let shade = apply_palette(self.bgp, color);
+        (shade, DMG_COLORS[shade as usize])
+    }
+
+    fn obj_color(&self, attributes: u8, color: u8) -> (u8, u16) {
+        if self.cgb_mode {
+            let rgb = palette_color(&self.obj_palette_ram, attributes & CGB_PALETTE, color);
+            return (color, rgb);
+        }
+
+        let palette = if attributes & OBJ_PALETTE != 0 {
+            self.obp1
+        } else {
+            self.obp0
+        };
+
+        let shade = apply_palette(palette, color);
+        (shade, DMG_COLORS[shade as usize])
+    }
+
+    fn set_pixel(&mut self, x: usize, shade: u8, color: u16) {
+        let index = self.ly as usize * SCREEN_WIDTH + x;
+
+        self.frame_buffer[index] = color;
+        self.shade_buffer[index] = shade;
+    }
+
+    // --- SGB ---
+    pub fn shades(&self) -> &[u8] {
+        &self.shade_buffer
+    }
+
+    pub fn screen_tile_data(&self) -> Vec<u8> {
+        // SGB VRAM transfers send the data of the first 256 tiles
+        // on screen, 20 per row, starting at the top left of the BG map
+
+        let tile_map = if self.lcdc & BG_TILE_MAP != 0 {
+            0x1C00
+        } else {
+            0x1800
+        };
+
+        (0..256)
+            .flat_map(|tile| {
+                let tile_index = self.video_ram[tile_map + (tile / 20) * 32 + tile % 20];
+                let tile_address = if self.lcdc & TILE_DATA != 0 {
+                    tile_index as usize * 16
+                } else {
+                    (0x1000 + (tile_index as i8 as i32) * 16) as usize
+                };
+
+                self.video_ram[tile_address..tile_address + 16].iter().copied()
+            })
+            .collect()
+    }
+}
+
+fn increment_palette_index(specification: u8) -> u8 {
+    // The index wraps around within the lower 6 bits
+    if specification & PALETTE_AUTO_INCREMENT == 0 {
+        return specification;
+    }
+
+    PALETTE_AUTO_INCREMENT | (specification.wrapping_add(1) & PALETTE_INDEX)
+}
+
+fn apply_palette(palette: u8, color: u8) -> u8 {
+    (palette >> (color * 2)) & 0b11
+}
+
+fn palette_color(palette_ram: &[u8; PALETTE_RAM_SIZE], palette: u8, color: u8) -> u16 {
+    // Each palette holds 4 little-endian RGB555 colors
+    let index = palette as usize * 8 + color as usize * 2;
+    u16::from_le_bytes([palette_ram[index], palette_ram[index + 1]]) & 0x7FFF
 }
@@ -0,0 +1,108 @@
+pub const HDMA1: u16 = 0xFF51;
+pub const HDMA2: u16 = 0xFF52;
+pub const HDMA3: u16 = 0xFF53;
+pub const HDMA4: u16 = 0xFF54;
+pub const HDMA5: u16 = 0xFF55;
+
+// Bit 7 of HDMA5 selects HBlank DMA instead of general purpose DMA
+const HBLANK_MODE: u8 = 0b1000_0000;
+const LENGTH_MASK: u8 = 0b0111_1111;
+
+pub const BLOCK_SIZE: u16 = 16;
+// The CPU is halted while a block is copied, for twice as many
+// M-cycles in double speed, as the copy takes the same time
+pub const BLOCK_M_CYCLES: u32 = 8;
+
+pub struct Hdma {
+    source: u16,
+    // Offset into VRAM, the upper 3 bits are ignored
+    destination: u16,
+    // Remaining 16-byte blocks
+    blocks: u8,
+    // An HBlank DMA is running and copies a block every HBlank
+    active: bool,
+}
+
+impl Hdma {
+    pub fn new() -> Self {
+        Self {
+            source: 0,
+            destination: 0,
+            blocks: 0,
+            active: false,
+        }
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        // Only HDMA5 is readable, holding the remaining length minus 1
+        // and bit 7 clear while an HBlank DMA is running. It reads
+        // 0xFF once a transfer is complete
+
+        match address {
+            HDMA5 => {
+                let length = self.blocks.wrapping_sub(1) & LENGTH_MASK;
+
+                if self.active {
+                    length
+                } else {
+                    HBLANK_MODE | length
+                }
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) -> bool {
+        // Returns whether a general purpose DMA was started,
+        // which copies all blocks at once
+
+        match address {
+            HDMA1 => self.source = (self.source & 0x00FF) | ((value as u16) << 8),
+            // The lower 4 bits of the addresses are ignored
+            HDMA2 => self.source = (self.source & 0xFF00) | (value as u16 & 0xF0),
+            HDMA3 => self.destination = (self.destination & 0x00FF) | ((value as u16 & 0x1F) << 8),
+            HDMA4 => self.destination = (self.destination & 0xFF00) | (value as u16 & 0xF0),
+            HDMA5 => {
+                // Writing with bit 7 clear during an HBlank DMA stops it
+                if self.active && value & HBLANK_MODE == 0 {
+                    self.active = false;
+                    return false;
+                }
+
+                self.blocks = (value & LENGTH_MASK) + 1;
+                self.active = value & HBLANK_MODE != 0;
+
+                return !self.active;
+            }
+            _ => unreachable!(),
+        }
+
+        false
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn next_block(&mut self) -> Option<(u16, u16)> {
+        // Returns the source address and the VRAM offset
+        // of the next block and advances both
+
+        if self.blocks == 0 {
+            self.active = false;
+            return None;
+        }
+
+        let block = (self.source, self.destination & 0x1FF0);
+
+        self.source = self.source.wrapping_add(BLOCK_SIZE);
+        self.destination = self.destination.wrapping_add(BLOCK_SIZE);
+        self.blocks -= 1;
+
+        if self.blocks == 0 {
+            self.active = false;
+        }
+
+        Some(block)
+    }
+}
@@ -0,0 +1,51 @@
+pub const INTERRUPT_FLAG: u16 = 0xFF0F;
+
+// Bits 5-7 of IF are unused and always read back as 1
+pub const INTERRUPT_FLAG_UNUSED: u8 = 0b1110_0000;
+pub const INTERRUPT_MASK: u8 = 0b0001_1111;
+
+// Ordered by priority, VBlank is served first
+pub const INTERRUPTS: [Interrupt; 5] = [
+    Interrupt::VBlank,
+    Interrupt::LcdStat,
+    Interrupt::Timer,
+    Interrupt::Serial,
+    Interrupt::Joypad,
+];
+
+#[derive(Copy, Clone, Debug)]
+pub enum Interrupt {
+    VBlank,
+    LcdStat,
+    Timer,
+    Serial,
+    Joypad,
+}
+
+impl Interrupt {
+    pub fn mask(&self) -> u8 {
+        match self {
+            Interrupt::VBlank => 0b0000_0001,
+            Interrupt::LcdStat => 0b0000_0010,
+            Interrupt::Timer => 0b0000_0100,
+            Interrupt::Serial => 0b0000_1000,
+            Interrupt::Joypad => 0b0001_0000,
+        }
+    }
+
+    pub fn vector(&self) -> u16 {
+        match self {
+            Interrupt::VBlank => 0x0040,
+            Interrupt::LcdStat => 0x0048,
+            Interrupt::Timer => 0x0050,
+            Interrupt::Serial => 0x0058,
+            Interrupt::Joypad => 0x0060,
+        }
+    }
+
+    pub fn highest_priority(pending: u8) -> Option<Self> {
+        INTERRUPTS
+            .into_iter()
+            .find(|interrupt| pending & interrupt.mask() != 0)
+    }
+}
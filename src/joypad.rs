@@ -0,0 +1,177 @@
+pub const JOYP: u16 = 0xFF00;
+
+// P14 selects the direction keys, P15 the action buttons.
+// Both select lines and the button lines are active-low
+const SELECT_DIRECTIONS: u8 = 0b0001_0000;
+const SELECT_ACTIONS: u8 = 0b0010_0000;
+const SELECT_MASK: u8 = 0b0011_0000;
+const JOYP_UNUSED: u8 = 0b1100_0000;
+const LINES_MASK: u8 = 0b0000_1111;
+
+// The SGB polls up to four joypads after MLT_REQ
+pub const MAX_PLAYERS: usize = 4;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+    Right,
+    Left,
+    Up,
+    Down,
+    A,
+    B,
+    Select,
+    Start,
+}
+
+impl Button {
+    fn mask(&self) -> u8 {
+        // Lower nibble holds the direction keys, the upper
+        // nibble the action buttons, both on lines P10-P13
+        match self {
+            Button::Right | Button::A => 0b0001,
+            Button::Left | Button::B => 0b0010,
+            Button::Up | Button::Select => 0b0100,
+            Button::Down | Button::Start => 0b1000,
+        }
+    }
+
+    fn is_direction(&self) -> bool {
+        matches!(
+            self,
+            Button::Right | Button::Left | Button::Up | Button::Down
+        )
+    }
+}
+
+pub struct Joypad {
+    select: u8,
+    // Pressed buttons are set to 1, unlike in the register
+    directions: [u8; MAX_PLAYERS],
+    actions: [u8; MAX_PLAYERS],
+    players: u8,
+    player: u8,
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Self {
+            select: SELECT_MASK,
+            directions: [0; MAX_PLAYERS],
+            actions: [0; MAX_PLAYERS],
+            players: 1,
+            player: 0,
+        }
+    }
+
+    pub fn read_byte(&self) -> u8 {
+        // With multiple joypads, deselecting both groups reads
+        // the ID of the current joypad, 0xF for the first one
+        if self.players > 1 && self.select == SELECT_MASK {
+            return JOYP_UNUSED | self.select | (LINES_MASK - self.player);
+        }
+
+        JOYP_UNUSED | self.select | self.lines()
+    }
+
+    pub fn write_byte(&mut self, value: u8) -> bool {
+        // Only the select lines are writable. Selecting a group
+        // with pressed buttons pulls lines low, which requests
+        // the joypad interrupt
+
+        let lines = self.lines();
+        let actions_selected = self.select & SELECT_ACTIONS == 0;
+        self.select = value & SELECT_MASK;
+
+        // The next joypad is selected when P15 goes high again
+        // and both groups are deselected
+        if self.players > 1 && actions_selected && self.select == SELECT_MASK {
+            self.player = (self.player + 1) % self.players;
+        }
+
+        is_falling_edge(lines, self.lines())
+    }
+
+    pub fn set_players(&mut self, players: u8) {
+        if players != self.players {
+            self.players = players;
+            self.player = 0;
+        }
+    }
+
+    pub fn set_button(&mut self, player: usize, button: Button, pressed: bool) -> bool {
+        // Returns whether the joypad interrupt is requested,
+        // which happens when any of P10-P13 goes from high to low
+
+        let lines = self.lines();
+
+        let group = if button.is_direction() {
+            &mut self.directions[player]
+        } else {
+            &mut self.actions[player]
+        };
+
+        if pressed {
+            *group |= button.mask();
+        } else {
+            *group &= !button.mask();
+        }
+
+        is_falling_edge(lines, self.lines())
+    }
+
+    fn lines(&self) -> u8 {
+        let mut pressed = 0;
+
+        if self.select & SELECT_DIRECTIONS == 0 {
+            pressed |= self.directions[self.player as usize];
+        }
+
+        if self.select & SELECT_ACTIONS == 0 {
+            pressed |= self.actions[self.player as usize];
+        }
+
+        !pressed & LINES_MASK
+    }
+}
+
+fn is_falling_edge(before: u8, after: u8) -> bool {
+    before & !after != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The select lines are active-low, so clearing one bit selects a group
+    const DIRECTIONS: u8 = SELECT_MASK & !SELECT_DIRECTIONS;
+    const ACTIONS: u8 = SELECT_MASK & !SELECT_ACTIONS;
+
+    #[test]
+    fn selected_group_pulls_lines_low() {
+        let mut joypad = Joypad::new();
+        joypad.set_button(0, Button::Down, true);
+        joypad.set_button(0, Button::A, true);
+
+        joypad.write_byte(DIRECTIONS);
+        assert_eq!(joypad.read_byte(), 0b1110_0111);
+
+        joypad.write_byte(ACTIONS);
+        assert_eq!(joypad.read_byte(), 0b1101_1110);
+
+        joypad.write_byte(SELECT_MASK);
+        assert_eq!(joypad.read_byte(), 0b1111_1111);
+    }
+
+    #[test]
+    fn press_requests_interrupt_only_when_selected() {
+        let mut joypad = Joypad::new();
+        joypad.write_byte(ACTIONS);
+
+        assert!(joypad.set_button(0, Button::Start, true));
+        assert!(!joypad.set_button(0, Button::Up, true));
+        assert!(!joypad.set_button(0, Button::Start, false));
+
+        // Selecting a group with a pressed button is a falling edge as well
+        assert!(joypad.write_byte(DIRECTIONS));
+    }
+}
@@ -0,0 +1,27 @@
+mod apu;
+mod cartridge;
+mod cpu;
+mod dma;
+mod gpu;
+mod hdma;
+mod instruction;
+mod interrupt;
+mod joypad;
+pub mod machine;
+mod memory_bus;
+mod model;
+mod png;
+mod registers;
+mod serial;
+mod sgb;
+mod timer;
+
+pub use crate::cartridge::{CartridgeEvent, CartridgeHeader, CgbSupport, HeaderError};
+pub use crate::gpu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+pub use crate::joypad::Button;
+pub use crate::memory_bus::{CGB_BOOT_ROM_SIZE, DMG_BOOT_ROM_SIZE};
+pub use crate::model::Model;
+pub use crate::serial::{
+    CaptureBuffer, Disconnected, GameBoyLink, GameBoyPrinter, SerialDevice, SocketLink, StdoutSink,
+};
+pub use crate::sgb::{SGB_SCREEN_HEIGHT, SGB_SCREEN_WIDTH};
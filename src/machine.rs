@@ -1,28 +1,318 @@
+use std::fmt;
 use std::fs::File;
-use std::io::LineWriter;
-use std::io::{Write, Error};
+use std::io::{self, LineWriter};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::{thread, time};
 
+use crate::cartridge::{CartridgeEvent, CartridgeHeader, CgbSupport, HeaderError};
 use crate::cpu::Cpu;
+use crate::joypad::{Button, MAX_PLAYERS};
+use crate::memory_bus::{CGB_BOOT_ROM_SIZE, DMG_BOOT_ROM_SIZE};
+use crate::model::Model;
+use crate::serial::{GameBoyLink, SerialDevice};
+
+pub const CPU_CLOCK_SPEED: u32 = 4_194_304;
+pub const CYCLES_PER_FRAME: u32 = 70_224;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    Header(HeaderError),
+    BootRomSize(usize),
+}
+
+impl From<HeaderError> for LoadError {
+    fn from(error: HeaderError) -> Self {
+        LoadError::Header(error)
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Header(error) => write!(f, "{}", error),
+            LoadError::BootRomSize(size) => write!(
+                f,
+                "Boot ROM is {} bytes, expected {} or {} bytes",
+                size, DMG_BOOT_ROM_SIZE, CGB_BOOT_ROM_SIZE
+            ),
+        }
+    }
+}
 
 pub struct Machine {
     cpu: Cpu,
 }
 
 impl Machine {
-    pub fn new(rom_data: Vec<u8>) -> Self {
-        Self {
-            cpu: Cpu::new(rom_data),
+    pub fn new(rom_data: Vec<u8>) -> Result<Self, LoadError> {
+        // Games with CGB support run on a CGB, everything else on a DMG
+        let model = match CartridgeHeader::parse(&rom_data) {
+            Ok(header) if header.cgb != CgbSupport::None => Model::Cgb,
+            _ => Model::Dmg,
+        };
+
+        Self::with_model(rom_data, model)
+    }
+
+    pub fn with_model(rom_data: Vec<u8>, model: Model) -> Result<Self, LoadError> {
+        // Starts at 0x0100 with the state the boot ROM of the model leaves behind
+
+        Ok(Self {
+            cpu: Cpu::new(rom_data, None, model)?,
+        })
+    }
+
+    pub fn with_boot_rom(
+        rom_data: Vec<u8>,
+        boot_rom: Vec<u8>,
+        model: Model,
+    ) -> Result<Self, LoadError> {
+        // Runs a DMG/MGB (256 bytes) or CGB (2304 bytes) boot image
+        // before the cartridge, instead of starting at 0x0100
+
+        if boot_rom.len() != DMG_BOOT_ROM_SIZE && boot_rom.len() != CGB_BOOT_ROM_SIZE {
+            return Err(LoadError::BootRomSize(boot_rom.len()));
+        }
+
+        Ok(Self {
+            cpu: Cpu::new(rom_data, Some(boot_rom), model)?,
+        })
+    }
+
+    pub fn run(&mut self, quit: &AtomicBool) -> io::Result<()> {
+        // A frame takes 70224 T-cycles, which results in a refresh rate
+        // of roughly 59.73 Hz. Runs until quit is set or the CPU locked
+        // up, then flushes the save and the serial device, as the
+        // process may exit right after
+        let frame_duration =
+            time::Duration::from_secs_f64(CYCLES_PER_FRAME as f64 / CPU_CLOCK_SPEED as f64);
+
+        while !quit.load(Ordering::Relaxed) && self.locked_opcode().is_none() {
+            let frame_start = time::Instant::now();
+            self.step_frame();
+
+            let elapsed = frame_start.elapsed();
+            if elapsed < frame_duration {
+                thread::sleep(frame_duration - elapsed);
+            }
+        }
+
+        self.cpu.memory_bus_mut().finish_serial();
+        self.flush_save()
+    }
+
+    pub fn step_frame(&mut self) {
+        // Runs until the PPU entered VBlank. While the LCD is
+        // off, a frame lasts the same amount of cycles
+
+        // Counted in half T-cycles, as the CPU runs twice as fast in double speed
+        let mut time = 0;
+
+        while time < CYCLES_PER_FRAME * 2 {
+            let cycles = self.step();
+            time += if self.cpu.memory_bus().is_double_speed() {
+                cycles
+            } else {
+                cycles * 2
+            };
+
+            if self.cpu.memory_bus_mut().take_frame_complete() {
+                break;
+            }
+        }
+    }
+
+    pub fn frame_buffer(&self) -> &[u16] {
+        // RGB555 colors (bits 0-4 red, 5-9 green, 10-14 blue),
+        // SCREEN_WIDTH * SCREEN_HEIGHT pixels
+
+        self.cpu.memory_bus().frame_buffer()
+    }
+
+    pub fn locked_opcode(&self) -> Option<u8> {
+        // The illegal opcode which hard-locked the CPU, if any.
+        // Only a new machine recovers from it
+
+        self.cpu.locked_opcode()
+    }
+
+    pub fn trace(&mut self, path: &Path) -> io::Result<()> {
+        // Writes a Gameboy Doctor compatible line with the
+        // registers before every executed instruction
+
+        let file = File::create(path)?;
+        self.cpu.set_trace(LineWriter::new(file));
+
+        Ok(())
+    }
+
+    pub fn read_byte(&self, address: u16) -> u8 {
+        // Memory as seen by the CPU, without side effects,
+        // e.g. for debuggers and tests
+
+        self.cpu.memory_bus().read_byte(address)
+    }
+
+    pub fn sgb_frame_buffer(&self) -> Option<&[u16]> {
+        // SGB_SCREEN_WIDTH * SGB_SCREEN_HEIGHT pixels of the game screen
+        // with SGB palettes and border, when running an SGB game on an SGB
+
+        self.cpu.memory_bus().sgb_frame_buffer()
+    }
+
+    pub fn set_button(&mut self, button: Button, pressed: bool) {
+        // Entry point for any input source, e.g. a keyboard
+        // frontend, scripted input or a test harness
+
+        self.cpu.memory_bus_mut().set_button(0, button, pressed);
+    }
+
+    pub fn set_player_button(&mut self, player: usize, button: Button, pressed: bool) {
+        // Joypads 2-4 are only polled by SGB games after MLT_REQ,
+        // player 0 is the same as set_button. Other players are ignored
+        if player >= MAX_PLAYERS {
+            return;
         }
+
+        self.cpu.memory_bus_mut().set_button(player, button, pressed);
+    }
+
+    pub fn connect_serial(&mut self, device: Box<dyn SerialDevice>) {
+        // Replaces whatever is plugged into the link port,
+        // which is disconnected by default
+
+        self.cpu.memory_bus_mut().connect_serial(device);
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        // Audio is resampled to 44100 Hz unless the frontend asks otherwise
+
+        self.cpu.memory_bus_mut().set_sample_rate(sample_rate);
+    }
+
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        // Interleaved left/right samples from -1.0 to 1.0 produced since
+        // the last call, meant to be queued to the audio device every frame
+
+        self.cpu.memory_bus_mut().drain_samples()
+    }
+
+    pub fn drain_samples_i16(&mut self) -> Vec<i16> {
+        self.drain_samples()
+            .into_iter()
+            .map(|sample| (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .collect()
     }
 
-    pub fn run(&mut self) {
-        let path = "log/lines.txt";
-        let file = File::create(path).expect("Could not create File.");
-        let mut file = LineWriter::new(file);
+    pub fn cartridge_header(&self) -> &CartridgeHeader {
+        self.cpu.memory_bus().cartridge_header()
+    }
+
+    pub fn load_save(&mut self, path: &Path) -> io::Result<()> {
+        // Battery-backed cartridges restore their RAM (and RTC) from this
+        // file and write back to it periodically and when dropped
+
+        self.cpu.memory_bus_mut().load_save(path)
+    }
+
+    pub fn flush_save(&mut self) -> io::Result<()> {
+        self.cpu.memory_bus_mut().flush_save()
+    }
+
+    pub fn drain_cartridge_events(&mut self) -> Vec<CartridgeEvent> {
+        // Host-facing cartridge hardware such as the rumble motor,
+        // in the order the game triggered it
+
+        self.cpu.memory_bus_mut().drain_cartridge_events()
+    }
+
+    fn step(&mut self) -> u32 {
+        // Every component is advanced by the T-cycles the CPU consumed.
+        // While HDMA halts the CPU, everything else keeps running
+
+        let cycles = self.cpu.step();
+        let memory_bus = self.cpu.memory_bus_mut();
+        memory_bus.tick(cycles);
+
+        let mut cycles = cycles as u32;
 
         loop {
-            self.cpu.step(&mut file);
+            let stall_cycles = memory_bus.take_stall_cycles();
+            if stall_cycles == 0 {
+                break;
+            }
+
+            for _ in 0..stall_cycles / 4 {
+                memory_bus.tick(4);
+            }
+
+            cycles += stall_cycles;
+        }
+
+        cycles
+    }
+}
+
+pub struct LinkedMachines {
+    machines: [Machine; 2],
+    // Time in half T-cycles each machine ran past the end of the
+    // last frame, so neither of them drifts ahead over time
+    time: [u64; 2],
+}
+
+impl LinkedMachines {
+    pub fn new(first: Machine, second: Machine) -> Self {
+        // Connects the serial ports of both machines with a cable,
+        // replacing whatever was plugged into them before
+
+        let mut machines = [first, second];
+        let (first_link, second_link) = GameBoyLink::pair();
+
+        machines[0].connect_serial(Box::new(first_link));
+        machines[1].connect_serial(Box::new(second_link));
+
+        Self {
+            machines,
+            time: [0; 2],
+        }
+    }
+
+    pub fn first(&mut self) -> &mut Machine {
+        &mut self.machines[0]
+    }
+
+    pub fn second(&mut self) -> &mut Machine {
+        &mut self.machines[1]
+    }
+
+    pub fn step_frame(&mut self) {
+        // Both machines run for the duration of a frame in lock-step,
+        // always stepping the one which is behind, so link transfers
+        // happen at the same point every time. Time is counted in
+        // half T-cycles, as the CPU runs twice as fast in double speed
+
+        let frame_time = CYCLES_PER_FRAME as u64 * 2;
+
+        while self.time[0] < frame_time || self.time[1] < frame_time {
+            let index = if self.time[0] <= self.time[1] { 0 } else { 1 };
+            let machine = &mut self.machines[index];
+
+            let cycles = machine.step() as u64;
+            self.time[index] += if machine.cpu.memory_bus().is_double_speed() {
+                cycles
+            } else {
+                cycles * 2
+            };
+        }
+
+        for time in self.time.iter_mut() {
+            *time -= frame_time;
+        }
+
+        // Frames aren't aligned to the LCD of either machine
+        for machine in self.machines.iter_mut() {
+            machine.cpu.memory_bus_mut().take_frame_complete();
         }
     }
 }
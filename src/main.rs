@@ -1,30 +1,146 @@
 use std::env;
-use std::fs::File;
-use std::io::Read;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
 
-mod cartridge;
-mod cpu;
-mod gpu;
-mod instruction;
-mod machine;
-mod memory_bus;
-mod registers;
-
-use crate::machine::Machine;
+use gemboi::machine::Machine;
+use gemboi::{CartridgeHeader, GameBoyPrinter, Model, SocketLink, StdoutSink, CGB_BOOT_ROM_SIZE};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    // A link cable to another instance is set up with --listen or
+    // --connect, followed by a TCP address like 127.0.0.1:5000 or
+    // unix:<path> for a Unix domain socket. --printer connects a
+    // Game Boy Printer instead, which writes PNG files to a directory.
+    // Without either, the bytes sent over the cable are printed.
+    // --model picks the hardware, e.g. to run an MGB or SGB boot ROM
+    let mut args = Vec::new();
+    let mut link = None;
+    let mut printer = None;
+    let mut model = None;
+
+    let mut all_args = env::args();
+    while let Some(arg) = all_args.next() {
+        match arg.as_str() {
+            "--listen" | "--connect" => {
+                let address = all_args.next().expect("Error: No link address provided.");
+                link = Some((arg == "--listen", address));
+            }
+            "--printer" => {
+                let directory = all_args.next().expect("Error: No printer directory provided.");
+                printer = Some(PathBuf::from(directory));
+            }
+            "--model" => {
+                let name = all_args.next().expect("Error: No model provided.");
+                model = Some(parse_model(&name).expect("Error: Unknown model."));
+            }
+            _ => args.push(arg),
+        }
+    }
+
     if args.len() < 2 {
         panic!("Error: No file path provided.");
     }
 
     let rom_path = "roms/".to_owned() + &args[1];
 
-    let mut file = File::open(rom_path).expect("Error: Can't open file.");
+    let mut file = File::open(&rom_path).expect("Error: Can't open file.");
     let mut rom_data = Vec::new();
     file.read_to_end(&mut rom_data)
         .expect("Error: Can't read file.");
 
-    let mut machine = Machine::new(rom_data);
-    machine.run();
+    // Bad dumps are only reported, as they often still run
+    if let Ok(header) = CartridgeHeader::parse(&rom_data) {
+        for error in header.validate(&rom_data) {
+            eprintln!("Warning: {}", error);
+        }
+    }
+
+    // An optional second argument is a boot ROM image to run first.
+    // Without --model, a CGB boot ROM selects a CGB and any other a DMG
+    let machine = match (args.get(2), model) {
+        (Some(boot_rom_path), model) => {
+            let boot_rom = fs::read(boot_rom_path).expect("Error: Can't read boot ROM.");
+            let model = model.unwrap_or(if boot_rom.len() == CGB_BOOT_ROM_SIZE {
+                Model::Cgb
+            } else {
+                Model::Dmg
+            });
+
+            Machine::with_boot_rom(rom_data, boot_rom, model)
+        }
+        (None, Some(model)) => Machine::with_model(rom_data, model),
+        (None, None) => Machine::new(rom_data),
+    };
+
+    let mut machine = match machine {
+        Ok(machine) => machine,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            process::exit(1);
+        }
+    };
+
+    machine
+        .load_save(&Path::new(&rom_path).with_extension("sav"))
+        .expect("Error: Can't read save file.");
+
+    if let Some((listen, address)) = link {
+        let link = open_link(listen, &address).expect("Error: Can't open link cable.");
+        machine.connect_serial(Box::new(link));
+    } else if let Some(directory) = printer {
+        fs::create_dir_all(&directory).expect("Error: Can't create printer directory.");
+        machine.connect_serial(Box::new(GameBoyPrinter::new(directory)));
+    } else {
+        machine.connect_serial(Box::new(StdoutSink));
+    }
+
+    // Pressing enter quits, which flushes the save file
+    let quit = Arc::new(AtomicBool::new(false));
+    let stdin_quit = Arc::clone(&quit);
+    thread::spawn(move || {
+        if io::stdin().read_line(&mut String::new()).is_ok_and(|read| read > 0) {
+            stdin_quit.store(true, Ordering::Relaxed);
+        }
+    });
+
+    machine.run(&quit).expect("Error: Can't write save file.");
+
+    if let Some(opcode) = machine.locked_opcode() {
+        eprintln!("Error: CPU locked up on illegal opcode {:#04X}", opcode);
+    }
+}
+
+fn open_link(listen: bool, address: &str) -> io::Result<SocketLink> {
+    #[cfg(unix)]
+    if let Some(path) = address.strip_prefix("unix:") {
+        let path = Path::new(path);
+
+        return if listen {
+            SocketLink::listen_unix(path)
+        } else {
+            SocketLink::connect_unix(path)
+        };
+    }
+
+    if listen {
+        SocketLink::listen_tcp(address)
+    } else {
+        SocketLink::connect_tcp(address)
+    }
+}
+
+fn parse_model(name: &str) -> Option<Model> {
+    match name {
+        "dmg0" => Some(Model::Dmg0),
+        "dmg" => Some(Model::Dmg),
+        "mgb" => Some(Model::Mgb),
+        "sgb" => Some(Model::Sgb),
+        "sgb2" => Some(Model::Sgb2),
+        "cgb" => Some(Model::Cgb),
+        _ => None,
+    }
 }
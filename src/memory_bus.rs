@@ -1,5 +1,17 @@
-use crate::cartridge::Cartridge;
-use crate::gpu::Gpu;
+use std::io;
+use std::path::Path;
+
+use crate::apu::{Apu, NR10, NR52, WAVE_RAM_END};
+use crate::cartridge::{Cartridge, CartridgeEvent, CartridgeHeader, CgbSupport, HeaderError};
+use crate::dma::{Dma, DMA};
+use crate::gpu::{Gpu, BCPS, BGP, LCDC, LYC, OCPD, VBK, WX};
+use crate::hdma::{Hdma, BLOCK_M_CYCLES, BLOCK_SIZE, HDMA1, HDMA5};
+use crate::interrupt::{Interrupt, INTERRUPT_FLAG, INTERRUPT_FLAG_UNUSED, INTERRUPT_MASK};
+use crate::joypad::{Button, Joypad, JOYP};
+use crate::model::Model;
+use crate::serial::{Serial, SerialDevice, SB, SC};
+use crate::sgb::Sgb;
+use crate::timer::{Timer, DIV, TAC};
 
 pub const CARTRIDGE_ROM_START: u16 = 0x0000;
 pub const CARTRIDGE_ROM_END: u16 = 0x7FFF;
@@ -13,6 +25,15 @@ pub const CARTRIDGE_RAM_END: u16 = 0xBFFF;
 pub const WRAM_START: u16 = 0xC000;
 pub const WRAM_END: u16 = 0xDFFF;
 
+const ECHO_RAM_START: u16 = 0xE000;
+const ECHO_RAM_END: u16 = 0xFDFF;
+
+const OAM_START: u16 = 0xFE00;
+const OAM_END: u16 = 0xFE9F;
+
+const NOT_USABLE_START: u16 = 0xFEA0;
+const NOT_USABLE_END: u16 = 0xFEFF;
+
 const IO_START: u16 = 0xFF00;
 const IO_END: u16 = 0xFF7F;
 
@@ -21,9 +42,32 @@ const HRAM_END: u16 = 0xFFFE;
 
 const INTERRUPT_ENABLE: u16 = 0xFFFF;
 
+// Set by the CGB boot ROM, bit 2 selects DMG compatibility mode
+// once the boot ROM is unmapped
+const KEY0: u16 = 0xFF4C;
+const KEY0_DMG_COMPATIBILITY: u8 = 0b0000_0100;
+
+// CGB speed switch, bit 7: current speed, bit 0: switch armed
+const KEY1: u16 = 0xFF4D;
+const KEY1_DOUBLE_SPEED: u8 = 0b1000_0000;
+const KEY1_ARMED: u8 = 0b0000_0001;
+
+// CGB WRAM bank select for 0xD000-0xDFFF
+const SVBK: u16 = 0xFF70;
+const WRAM_BANK_SIZE: usize = 0x1000;
+const WRAM_BANKS: usize = 8;
 
 pub const BOOT_ROM_END: u16 = 0x100;
 
+// CGB boot ROMs are 2304 bytes and also cover 0x0200-0x08FF,
+// which leaves the cartridge header at 0x0100-0x01FF visible
+const CGB_BOOT_ROM_START: u16 = 0x200;
+pub const DMG_BOOT_ROM_SIZE: usize = 0x100;
+pub const CGB_BOOT_ROM_SIZE: usize = 0x900;
+
+// Writing a non-zero value unmaps the boot ROM until the next reset
+const BOOT: u16 = 0xFF50;
+
 /*
   0000-3FFF   16KB ROM Bank 00     (in cartridge, fixed at bank 00)
   4000-7FFF   16KB ROM Bank 01..NN (in cartridge, switchable bank number)
@@ -41,61 +85,654 @@ pub const BOOT_ROM_END: u16 = 0x100;
 
 pub struct MemoryBus {
     cartridge: Cartridge,
+    boot_rom: Option<Vec<u8>>,
     gpu: Gpu,
-    wram: [u8; 8192],
+    apu: Apu,
+    timer: Timer,
+    dma: Dma,
+    joypad: Joypad,
+    serial: Serial,
+    hdma: Hdma,
+    // Only present when an SGB runs a game with SGB support
+    sgb: Option<Box<Sgb>>,
+    // Bank 0 is fixed at 0xC000, banks 1-7 are switchable in CGB mode
+    wram: Box<[u8]>,
+    wram_bank: u8,
+    cgb_mode: bool,
+    key0: u8,
+    double_speed: bool,
+    speed_switch_armed: bool,
+    // T-cycles at double speed which don't add up to a full M-cycle
+    // of the components running at normal speed yet
+    half_cycles: u8,
+    // T-cycles the CPU is halted for by HDMA
+    stall_cycles: u32,
     pub io: [u8; 128],
     hram: [u8; 128],
+    interrupt_flag: u8,
     interrupt_enable: u8,
 }
 
 impl MemoryBus {
-    pub fn new(rom_data: Vec<u8>) -> Self {
-        let cartridge = Cartridge::build(rom_data);
+    pub fn new(
+        rom_data: Vec<u8>,
+        boot_rom: Option<Vec<u8>>,
+        model: Model,
+    ) -> Result<Self, HeaderError> {
+        let cartridge = Cartridge::build(rom_data)?;
+
+        // CGB features are only enabled for games which support them,
+        // and for the CGB boot ROM until it picks the mode on unmapping
+        let cgb_mode =
+            model.is_cgb() && (boot_rom.is_some() || cartridge.header.cgb != CgbSupport::None);
 
-        Self {
+        // The SGB only accepts commands from games which set the
+        // SGB flag and use the old licensee code 0x33
+        let header = &cartridge.header;
+        let sgb = if model.is_sgb() && header.sgb && header.old_licensee_code == 0x33 {
+            Some(Box::new(Sgb::new()))
+        } else {
+            None
+        };
+
+        let mut memory_bus = Self {
             cartridge,
-            gpu: Gpu::new(),
-            wram: [0; 8192],
+            boot_rom,
+            gpu: Gpu::new(model, cgb_mode),
+            apu: Apu::new(model),
+            timer: Timer::new(model),
+            dma: Dma::new(),
+            joypad: Joypad::new(),
+            serial: Serial::new(cgb_mode),
+            hdma: Hdma::new(),
+            sgb,
+            wram: vec![0; WRAM_BANK_SIZE * WRAM_BANKS].into_boxed_slice(),
+            wram_bank: 1,
+            cgb_mode,
+            key0: 0,
+            double_speed: false,
+            speed_switch_armed: false,
+            half_cycles: 0,
+            stall_cycles: 0,
             io: [0; 128],
             hram: [0; 128],
+            // The boot ROM finishes with a pending VBlank interrupt
+            interrupt_flag: Interrupt::VBlank.mask(),
             interrupt_enable: 0,
+        };
+
+        if memory_bus.boot_rom.is_some() {
+            memory_bus.reset_io();
         }
+
+        Ok(memory_bus)
+    }
+
+    fn reset_io(&mut self) {
+        // The components start with the values the boot ROM leaves
+        // behind, so the power-on state is restored for it to set up
+
+        self.write_io(DIV, 0x00);
+        self.write_io(LCDC, 0x00);
+        self.write_io(BGP, 0x00);
+        self.write_io(NR52, 0x00);
+        self.write_io(INTERRUPT_FLAG, 0x00);
     }
 
     pub fn read_byte(&self, address: u16) -> u8 {
-        if address == 0xFF44 {
-            return 0x90
+        if self.is_dma_conflict(address) {
+            return 0xFF;
         }
+
         match address {
-            CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => self.cartridge.read(address),
+            CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => self.read_rom(address),
             VRAM_START..=VRAM_END => self.gpu.read_byte(address - VRAM_START),
             CARTRIDGE_RAM_START..=CARTRIDGE_RAM_END => self.cartridge.read(address),
-            WRAM_START..=WRAM_END => self.wram[address as usize - WRAM_START as usize],
-            IO_START..=IO_END => self.io[address as usize - IO_START as usize],
+            WRAM_START..=WRAM_END => self.wram[self.wram_index(address - WRAM_START)],
+            // Echo RAM mirrors 0xC000-0xDDFF
+            ECHO_RAM_START..=ECHO_RAM_END => self.wram[self.wram_index(address - ECHO_RAM_START)],
+            OAM_START..=OAM_END => self.gpu.read_oam(address - OAM_START),
+            // Reads from the unusable area return 0x00 on DMG
+            NOT_USABLE_START..=NOT_USABLE_END => 0x00,
+            IO_START..=IO_END => self.read_io(address),
             HRAM_START..=HRAM_END => self.hram[address as usize - HRAM_START as usize],
             INTERRUPT_ENABLE => self.interrupt_enable,
-            _ => {
-                println!("Unknown address: {:#X} Can't read byte.", address);
-                0x00
-            }
         }
     }
 
     pub fn write_byte(&mut self, address: u16, value: u8) {
+        if self.is_dma_conflict(address) {
+            return;
+        }
+
         match address {
             CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => self.cartridge.write(address, value),
             VRAM_START..=VRAM_END => self.gpu.write_byte(address - VRAM_START, value),
             CARTRIDGE_RAM_START..=CARTRIDGE_RAM_END => self.cartridge.write(address, value),
-            WRAM_START..=WRAM_END => self.wram[address as usize - WRAM_START as usize] = value,
-            IO_START..=IO_END => {
-                if address as usize - IO_START as usize == 1 {
-                    print!("{}", char::from(value));
-                }
-                self.io[address as usize - IO_START as usize] = value
-            },
+            WRAM_START..=WRAM_END => {
+                let index = self.wram_index(address - WRAM_START);
+                self.wram[index] = value;
+            }
+            ECHO_RAM_START..=ECHO_RAM_END => {
+                let index = self.wram_index(address - ECHO_RAM_START);
+                self.wram[index] = value;
+            }
+            OAM_START..=OAM_END => self.gpu.write_oam(address - OAM_START, value),
+            // Writes to the unusable area are ignored
+            NOT_USABLE_START..=NOT_USABLE_END => {}
+            IO_START..=IO_END => self.write_io(address, value),
             HRAM_START..=HRAM_END => self.hram[address as usize - HRAM_START as usize] = value,
             INTERRUPT_ENABLE => self.interrupt_enable = value,
-            _ => panic!("Unknown address: {:#X} Can't write byte.", address),
         }
     }
+
+    fn read_io(&self, address: u16) -> u8 {
+        match address {
+            JOYP => self.joypad.read_byte(),
+            SB..=SC => self.serial.read_register(address),
+            DIV..=TAC => self.timer.read_byte(address),
+            INTERRUPT_FLAG => self.interrupt_flag | INTERRUPT_FLAG_UNUSED,
+            LCDC..=LYC | BGP..=WX | VBK | BCPS..=OCPD => self.gpu.read_register(address),
+            DMA => self.dma.read_register(),
+            // The remaining CGB registers read 0xFF outside of CGB mode
+            KEY1 | HDMA1..=HDMA5 | SVBK if !self.cgb_mode => 0xFF,
+            KEY1 => {
+                let speed = if self.double_speed { KEY1_DOUBLE_SPEED } else { 0 };
+                0b0111_1110 | speed | self.speed_switch_armed as u8
+            }
+            HDMA1..=HDMA5 => self.hdma.read_register(address),
+            SVBK => 0b1111_1000 | self.wram_bank,
+            KEY0 | BOOT => 0xFF,
+            NR10..=WAVE_RAM_END => self.apu.read_register(address),
+            _ => self.io[address as usize - IO_START as usize],
+        }
+    }
+
+    fn write_io(&mut self, address: u16, value: u8) {
+        match address {
+            JOYP => {
+                if self.joypad.write_byte(value) {
+                    self.request_interrupt(Interrupt::Joypad);
+                }
+
+                if let Some(ref mut sgb) = self.sgb {
+                    sgb.write_joypad(value);
+                    self.joypad.set_players(sgb.players());
+                }
+            }
+            SB..=SC => self.serial.write_register(address, value),
+            DIV..=TAC => self.timer.write_byte(address, value),
+            INTERRUPT_FLAG => self.interrupt_flag = value & INTERRUPT_MASK,
+            LCDC..=LYC | BGP..=WX | VBK | BCPS..=OCPD => self.gpu.write_register(address, value),
+            DMA => self.dma.start(value),
+            KEY1 | HDMA1..=HDMA5 | SVBK if !self.cgb_mode => {}
+            KEY1 => self.speed_switch_armed = value & KEY1_ARMED != 0,
+            HDMA1..=HDMA5 => {
+                // General purpose DMA copies everything at once
+                if self.hdma.write_register(address, value) {
+                    while self.copy_hdma_block() {}
+                }
+            }
+            SVBK => {
+                // Bank 0 selects bank 1 as well
+                self.wram_bank = (value & 0b0111).max(1);
+            }
+            // KEY0 can only be written by the boot ROM
+            KEY0 if self.boot_rom.is_some() => self.key0 = value,
+            KEY0 => {}
+            BOOT => {
+                if value != 0 && self.boot_rom.take().is_some() {
+                    self.select_cgb_mode();
+                }
+            }
+            NR10..=WAVE_RAM_END => self.apu.write_register(address, value),
+            _ => self.io[address as usize - IO_START as usize] = value,
+        }
+    }
+
+    fn select_cgb_mode(&mut self) {
+        // The CGB runs games without CGB support in DMG compatibility
+        // mode, which disables the CGB registers from then on
+
+        let dmg_compatibility = self.key0 & KEY0_DMG_COMPATIBILITY != 0
+            || self.cartridge.header.cgb == CgbSupport::None;
+
+        if self.cgb_mode && dmg_compatibility {
+            self.cgb_mode = false;
+            self.wram_bank = 1;
+            self.gpu.set_cgb_mode(false);
+            self.serial.set_cgb_mode(false);
+        }
+    }
+
+    fn read_rom(&self, address: u16) -> u8 {
+        // The boot ROM is overlaid on the cartridge while it is mapped
+
+        if let Some(ref boot_rom) = self.boot_rom {
+            let is_header = (BOOT_ROM_END..CGB_BOOT_ROM_START).contains(&address);
+
+            if (address as usize) < boot_rom.len() && !is_header {
+                return boot_rom[address as usize];
+            }
+        }
+
+        self.cartridge.read(address)
+    }
+
+    pub fn tick(&mut self, cycles: u8) {
+        // In double speed mode, the timer, serial port and OAM DMA run
+        // along with the CPU, while the PPU, APU and RTC keep their normal speed
+
+        self.tick_dma(cycles);
+
+        if self.timer.tick(cycles) {
+            self.request_interrupt(Interrupt::Timer);
+        }
+
+        if self.serial.tick(cycles) {
+            self.request_interrupt(Interrupt::Serial);
+        }
+
+        let cycles = self.normal_speed_cycles(cycles);
+
+        let interrupts = self.gpu.tick(cycles);
+        self.interrupt_flag |= interrupts;
+
+        if let Some(ref mut sgb) = self.sgb {
+            if interrupts & Interrupt::VBlank.mask() != 0 {
+                sgb.finish_frame(&self.gpu);
+            }
+        }
+
+        self.apu.tick(cycles);
+        self.cartridge.tick(cycles);
+
+        // HBlank DMA copies one block at the start of every HBlank
+        if self.gpu.hblank_started {
+            self.gpu.hblank_started = false;
+
+            if self.hdma.is_active() {
+                self.copy_hdma_block();
+            }
+        }
+    }
+
+    fn normal_speed_cycles(&mut self, cycles: u8) -> u8 {
+        if !self.double_speed {
+            return cycles;
+        }
+
+        self.half_cycles += cycles / 2;
+        let whole = self.half_cycles & !0b11;
+        self.half_cycles -= whole;
+
+        whole
+    }
+
+    pub fn is_double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    pub fn switch_speed(&mut self) -> bool {
+        // Called on STOP, which switches the CPU speed instead of
+        // stopping if armed through KEY1. The divider is reset as well
+
+        if !self.cgb_mode || !self.speed_switch_armed {
+            return false;
+        }
+
+        self.double_speed = !self.double_speed;
+        self.speed_switch_armed = false;
+        self.timer.write_byte(DIV, 0);
+
+        true
+    }
+
+    fn copy_hdma_block(&mut self) -> bool {
+        // Returns whether a block was copied
+
+        let Some((source, destination)) = self.hdma.next_block() else {
+            return false;
+        };
+
+        for offset in 0..BLOCK_SIZE {
+            let value = self.read_dma_source(source.wrapping_add(offset));
+            self.gpu.write_byte((destination + offset) & 0x1FFF, value);
+        }
+
+        let m_cycles = if self.double_speed {
+            BLOCK_M_CYCLES * 2
+        } else {
+            BLOCK_M_CYCLES
+        };
+        self.stall_cycles += m_cycles * 4;
+
+        true
+    }
+
+    pub fn take_stall_cycles(&mut self) -> u32 {
+        std::mem::take(&mut self.stall_cycles)
+    }
+
+    fn wram_index(&self, offset: u16) -> usize {
+        // 0xD000-0xDFFF maps the selected bank
+
+        let offset = offset as usize;
+
+        if offset < WRAM_BANK_SIZE {
+            offset
+        } else {
+            self.wram_bank as usize * WRAM_BANK_SIZE + (offset - WRAM_BANK_SIZE)
+        }
+    }
+
+    fn tick_dma(&mut self, cycles: u8) {
+        // OAM DMA copies one byte per M-cycle, 160 bytes in total
+
+        for _ in 0..cycles / 4 {
+            if let Some((source, offset)) = self.dma.step() {
+                let value = self.read_dma_source(source);
+                self.gpu.write_oam(offset, value);
+            }
+        }
+    }
+
+    fn read_dma_source(&self, address: u16) -> u8 {
+        // Sources above 0xDFFF are mapped onto WRAM like echo RAM
+
+        match address {
+            CARTRIDGE_ROM_START..=CARTRIDGE_ROM_END => self.read_rom(address),
+            VRAM_START..=VRAM_END => self.gpu.read_byte(address - VRAM_START),
+            CARTRIDGE_RAM_START..=CARTRIDGE_RAM_END => self.cartridge.read(address),
+            WRAM_START..=WRAM_END => self.wram[self.wram_index(address - WRAM_START)],
+            _ => self.wram[self.wram_index(address - ECHO_RAM_START)],
+        }
+    }
+
+    fn is_dma_conflict(&self, address: u16) -> bool {
+        // While OAM DMA is running, the external and video buses are
+        // occupied, so the CPU can only access I/O registers and HRAM
+
+        self.dma.is_active() && address < IO_START
+    }
+
+    pub fn set_button(&mut self, player: usize, button: Button, pressed: bool) {
+        if self.joypad.set_button(player, button, pressed) {
+            self.request_interrupt(Interrupt::Joypad);
+        }
+    }
+
+    pub fn connect_serial(&mut self, device: Box<dyn SerialDevice>) {
+        self.serial.connect(device);
+    }
+
+    pub fn finish_serial(&mut self) {
+        self.serial.finish();
+    }
+
+    pub fn sgb_frame_buffer(&self) -> Option<&[u16]> {
+        self.sgb.as_ref().map(|sgb| &sgb.frame_buffer[..])
+    }
+
+    pub fn cartridge_header(&self) -> &CartridgeHeader {
+        &self.cartridge.header
+    }
+
+    pub fn load_save(&mut self, path: &Path) -> io::Result<()> {
+        self.cartridge.load_save(path)
+    }
+
+    pub fn flush_save(&mut self) -> io::Result<()> {
+        self.cartridge.flush_save()
+    }
+
+    pub fn drain_cartridge_events(&mut self) -> Vec<CartridgeEvent> {
+        self.cartridge.drain_events()
+    }
+
+    pub fn request_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt_flag |= interrupt.mask();
+    }
+
+    pub fn clear_interrupt(&mut self, interrupt: Interrupt) {
+        self.interrupt_flag &= !interrupt.mask();
+    }
+
+    pub fn is_requested(&self, interrupt: Interrupt) -> bool {
+        self.interrupt_flag & interrupt.mask() != 0
+    }
+
+    pub fn get_pending_interrupts(&self) -> u8 {
+        self.interrupt_flag & self.interrupt_enable & INTERRUPT_MASK
+    }
+
+    pub fn frame_buffer(&self) -> &[u16] {
+        &self.gpu.frame_buffer
+    }
+
+    pub fn take_frame_complete(&mut self) -> bool {
+        // Returns whether the PPU entered VBlank since the last call
+        std::mem::take(&mut self.gpu.frame_complete)
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.apu.set_sample_rate(sample_rate);
+    }
+
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        self.apu.drain_samples()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::joypad::Button;
+    use crate::serial::CaptureBuffer;
+    use crate::sgb::tests::command_pulses;
+
+    fn memory_bus() -> MemoryBus {
+        MemoryBus::new(vec![0; 0x8000], None, Model::Dmg).unwrap()
+    }
+
+    #[test]
+    fn echo_ram_mirrors_wram() {
+        let mut memory_bus = memory_bus();
+
+        memory_bus.write_byte(0xC123, 0x42);
+        memory_bus.write_byte(0xFDFF, 0x99);
+
+        assert_eq!(memory_bus.read_byte(0xE123), 0x42);
+        assert_eq!(memory_bus.read_byte(0xDDFF), 0x99);
+    }
+
+    #[test]
+    fn unusable_area_ignores_writes() {
+        let mut memory_bus = memory_bus();
+
+        memory_bus.write_byte(0xFEA0, 0x42);
+        memory_bus.write_byte(0xFEFF, 0x42);
+
+        assert_eq!(memory_bus.read_byte(0xFEA0), 0x00);
+        assert_eq!(memory_bus.read_byte(0xFEFF), 0x00);
+    }
+
+    #[test]
+    fn serial_transfer_takes_8_bit_periods() {
+        let mut memory_bus = memory_bus();
+        let capture = CaptureBuffer::new();
+        let bytes = capture.bytes();
+        memory_bus.connect_serial(Box::new(capture));
+
+        memory_bus.write_byte(SB, 0x42);
+        memory_bus.write_byte(SC, 0x81);
+
+        for _ in 0..8 * 512 / 4 - 1 {
+            memory_bus.tick(4);
+        }
+
+        assert_eq!(memory_bus.read_byte(SC) & 0x80, 0x80);
+        assert_eq!(memory_bus.read_byte(INTERRUPT_FLAG) & Interrupt::Serial.mask(), 0);
+
+        memory_bus.tick(4);
+
+        assert_eq!(memory_bus.read_byte(SC) & 0x80, 0);
+        assert_ne!(memory_bus.read_byte(INTERRUPT_FLAG) & Interrupt::Serial.mask(), 0);
+        assert_eq!(memory_bus.read_byte(SB), 0xFF);
+        assert_eq!(*bytes.borrow(), [0x42]);
+    }
+
+    fn cgb_memory_bus() -> MemoryBus {
+        // The LCD is turned off, so VRAM is always accessible
+
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x0143] = 0x80;
+
+        let mut memory_bus = MemoryBus::new(rom_data, None, Model::Cgb).unwrap();
+        memory_bus.write_byte(LCDC, 0x00);
+
+        memory_bus
+    }
+
+    #[test]
+    fn cgb_registers_read_high_in_dmg_mode() {
+        let mut memory_bus = memory_bus();
+        memory_bus.write_byte(SVBK, 0x02);
+
+        assert_eq!(memory_bus.read_byte(KEY1), 0xFF);
+        assert_eq!(memory_bus.read_byte(SVBK), 0xFF);
+    }
+
+    #[test]
+    fn svbk_switches_upper_wram_bank() {
+        let mut memory_bus = cgb_memory_bus();
+
+        for bank in 1..8 {
+            memory_bus.write_byte(SVBK, bank);
+            memory_bus.write_byte(0xD000, bank);
+        }
+
+        // Bank 0 selects bank 1 as well
+        memory_bus.write_byte(SVBK, 0x00);
+        assert_eq!(memory_bus.read_byte(SVBK), 0xF9);
+        assert_eq!(memory_bus.read_byte(0xD000), 0x01);
+
+        memory_bus.write_byte(SVBK, 0x05);
+        assert_eq!(memory_bus.read_byte(0xD000), 0x05);
+        assert_eq!(memory_bus.read_byte(0xF000), 0x05);
+    }
+
+    #[test]
+    fn cgb_boot_rom_selects_dmg_compatibility_on_unmapping() {
+        let boot_rom = vec![0; CGB_BOOT_ROM_SIZE];
+        let mut memory_bus = MemoryBus::new(vec![0; 0x8000], Some(boot_rom), Model::Cgb).unwrap();
+
+        memory_bus.write_byte(SVBK, 0x02);
+        assert_eq!(memory_bus.read_byte(SVBK), 0xFA);
+
+        memory_bus.write_byte(BOOT, 0x01);
+        assert_eq!(memory_bus.read_byte(SVBK), 0xFF);
+        assert_eq!(memory_bus.read_byte(VBK), 0xFF);
+    }
+
+    #[test]
+    fn key0_selects_dmg_compatibility_for_cgb_games() {
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x0143] = 0x80;
+        let boot_rom = vec![0; CGB_BOOT_ROM_SIZE];
+
+        let mut cgb_game =
+            MemoryBus::new(rom_data.clone(), Some(boot_rom.clone()), Model::Cgb).unwrap();
+        cgb_game.write_byte(BOOT, 0x01);
+        assert_eq!(cgb_game.read_byte(SVBK), 0xF9);
+
+        let mut dmg_game = MemoryBus::new(rom_data, Some(boot_rom), Model::Cgb).unwrap();
+        dmg_game.write_byte(KEY0, KEY0_DMG_COMPATIBILITY);
+        dmg_game.write_byte(BOOT, 0x01);
+        assert_eq!(dmg_game.read_byte(SVBK), 0xFF);
+    }
+
+    #[test]
+    fn vbk_switches_vram_bank() {
+        let mut memory_bus = cgb_memory_bus();
+
+        memory_bus.write_byte(VBK, 0x01);
+        memory_bus.write_byte(0x9800, 0x42);
+        memory_bus.write_byte(VBK, 0x00);
+
+        assert_eq!(memory_bus.read_byte(0x9800), 0x00);
+    }
+
+    #[test]
+    fn palette_index_auto_increments() {
+        let mut memory_bus = cgb_memory_bus();
+
+        memory_bus.write_byte(BCPS, 0x82);
+        memory_bus.write_byte(BCPS + 1, 0x1F);
+        memory_bus.write_byte(BCPS + 1, 0x7C);
+        assert_eq!(memory_bus.read_byte(BCPS) & 0x3F, 0x04);
+
+        memory_bus.write_byte(BCPS, 0x03);
+        assert_eq!(memory_bus.read_byte(BCPS + 1), 0x7C);
+    }
+
+    #[test]
+    fn general_purpose_hdma_copies_and_stalls_cpu() {
+        let mut memory_bus = cgb_memory_bus();
+
+        for offset in 0..0x20 {
+            memory_bus.write_byte(0xC000 + offset, offset as u8);
+        }
+
+        memory_bus.write_byte(HDMA1, 0xC0);
+        memory_bus.write_byte(HDMA1 + 1, 0x00);
+        memory_bus.write_byte(HDMA1 + 2, 0x81);
+        memory_bus.write_byte(HDMA1 + 3, 0x00);
+        memory_bus.write_byte(HDMA5, 0x01);
+
+        assert_eq!(memory_bus.read_byte(0x811F), 0x1F);
+        assert_eq!(memory_bus.read_byte(HDMA5), 0xFF);
+        assert_eq!(memory_bus.take_stall_cycles(), 2 * BLOCK_M_CYCLES * 4);
+        assert_eq!(memory_bus.take_stall_cycles(), 0);
+    }
+
+    #[test]
+    fn hdma_stall_doubles_in_double_speed() {
+        let mut memory_bus = cgb_memory_bus();
+
+        memory_bus.write_byte(KEY1, 0x01);
+        assert!(memory_bus.switch_speed());
+        assert_eq!(memory_bus.read_byte(KEY1), 0xFE);
+
+        memory_bus.write_byte(HDMA5, 0x00);
+        assert_eq!(memory_bus.take_stall_cycles(), 2 * BLOCK_M_CYCLES * 4);
+    }
+
+    #[test]
+    fn mlt_req_cycles_through_joypad_ids() {
+        // SGB support needs the header flag and the new licensee code
+        let mut rom_data = vec![0; 0x8000];
+        rom_data[0x0146] = 0x03;
+        rom_data[0x014B] = 0x33;
+
+        let mut memory_bus = MemoryBus::new(rom_data, None, Model::Sgb).unwrap();
+        memory_bus.set_button(1, Button::Start, true);
+
+        // MLT_REQ for two players
+        for value in command_pulses(0x11, &[0x01]) {
+            memory_bus.write_byte(JOYP, value);
+        }
+        assert_eq!(memory_bus.read_byte(JOYP), 0xFF);
+
+        // Pulsing P15 selects the next joypad
+        memory_bus.write_byte(JOYP, 0x10);
+        memory_bus.write_byte(JOYP, 0x30);
+        assert_eq!(memory_bus.read_byte(JOYP), 0xFE);
+
+        memory_bus.write_byte(JOYP, 0x10);
+        assert_eq!(memory_bus.read_byte(JOYP), 0xD7);
+
+        memory_bus.write_byte(JOYP, 0x30);
+        assert_eq!(memory_bus.read_byte(JOYP), 0xFF);
+    }
 }
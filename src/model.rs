@@ -0,0 +1,26 @@
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Model {
+    // Original Game Boy with the early boot ROM
+    Dmg0,
+    Dmg,
+    // Game Boy Pocket and Light
+    Mgb,
+    Sgb,
+    Sgb2,
+    Cgb,
+}
+
+impl Model {
+    pub fn is_sgb(&self) -> bool {
+        matches!(self, Model::Sgb | Model::Sgb2)
+    }
+
+    pub fn is_cgb(&self) -> bool {
+        matches!(self, Model::Cgb)
+    }
+
+    pub fn has_boot_chime(&self) -> bool {
+        // The SGB boot ROM leaves sound to the SNES
+        !self.is_sgb()
+    }
+}
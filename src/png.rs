@@ -0,0 +1,175 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+const BIT_DEPTH: u8 = 8;
+const GRAYSCALE: u8 = 0;
+
+// zlib header for deflate with a 32K window and no compression,
+// the data is split into stored blocks of at most 65535 bytes
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x01];
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+const FINAL_BLOCK: u8 = 0b1;
+
+// Every scanline starts with its filter type
+const FILTER_NONE: u8 = 0;
+
+pub fn write_grayscale(path: &Path, width: usize, height: usize, pixels: &[u8]) -> io::Result<()> {
+    // Writes an 8-bit grayscale image, one byte per pixel
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(&SIGNATURE)?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // Bit depth, color type, compression, filter and interlace method
+    header.extend_from_slice(&[BIT_DEPTH, GRAYSCALE, 0, 0, 0]);
+    write_chunk(&mut writer, b"IHDR", &header)?;
+
+    let mut scanlines = Vec::with_capacity((width + 1) * height);
+    for line in pixels.chunks_exact(width) {
+        scanlines.push(FILTER_NONE);
+        scanlines.extend_from_slice(line);
+    }
+    write_chunk(&mut writer, b"IDAT", &zlib_stored(&scanlines))?;
+
+    write_chunk(&mut writer, b"IEND", &[])?;
+
+    writer.flush()
+}
+
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    // The CRC covers the chunk type and data, but not the length
+
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.finish().to_be_bytes())
+}
+
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len().div_ceil(MAX_STORED_BLOCK).max(1);
+    let mut stream = Vec::with_capacity(data.len() + blocks * 5 + 6);
+    stream.extend_from_slice(&ZLIB_HEADER);
+
+    // An empty input still needs a single, empty final block
+    let mut chunks: Vec<&[u8]> = data.chunks(MAX_STORED_BLOCK).collect();
+    if chunks.is_empty() {
+        chunks.push(&[]);
+    }
+
+    let last = chunks.len() - 1;
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        // Stored blocks have the length followed by its complement
+        let length = chunk.len() as u16;
+
+        stream.push(if index == last { FINAL_BLOCK } else { 0 });
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(chunk);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+
+    stream
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % MODULO;
+        b = (b + a) % MODULO;
+    }
+
+    (b << 16) | a
+}
+
+struct Crc32 {
+    table: [u32; 256],
+    value: u32,
+}
+
+impl Crc32 {
+    fn new() -> Self {
+        // Reflected CRC-32 as used by PNG and zlib
+        const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+        let mut table = [0; 256];
+        for (index, entry) in table.iter_mut().enumerate() {
+            let mut value = index as u32;
+            for _ in 0..8 {
+                value = if value & 1 != 0 {
+                    POLYNOMIAL ^ (value >> 1)
+                } else {
+                    value >> 1
+                };
+            }
+            *entry = value;
+        }
+
+        Self {
+            table,
+            value: 0xFFFF_FFFF,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            let index = (self.value ^ byte as u32) & 0xFF;
+            self.value = self.table[index as usize] ^ (self.value >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.value ^ 0xFFFF_FFFF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn adler32_matches_reference() {
+        assert_eq!(adler32(b""), 0x0000_0001);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn empty_zlib_stream_has_final_block() {
+        let stream = zlib_stored(&[]);
+
+        assert_eq!(
+            stream,
+            [0x78, 0x01, 0x01, 0x00, 0x00, 0xFF, 0xFF, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn chunk_ends_with_crc() {
+        let mut chunk = Vec::new();
+        write_chunk(&mut chunk, b"IEND", &[]).unwrap();
+
+        assert_eq!(
+            chunk,
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 mod flags_register;
 
 use crate::registers::flags_register::FlagsRegister;
+use crate::cartridge::{CartridgeHeader, CgbSupport};
 use crate::instruction::Target;
+use crate::model::Model;
 
 pub struct Registers {
     a: u8,
@@ -15,17 +17,50 @@ pub struct Registers {
 }
 
 impl Registers {
-    pub fn new(enable_flags: bool) -> Self {
-        Self {
-            a: 0x01,
+    pub fn new(model: Model, header: &CartridgeHeader) -> Self {
+        // Values left behind by the boot ROM of each model, which
+        // games use to detect the hardware they are running on.
+        // If the header checksum is 0x00, then the carry and
+        // half-carry flags are clear; otherwise, they are both set
+        let enable_flags = header.header_checksum != 0x00;
+
+        let (a, f, bc, de, hl) = match model {
+            Model::Dmg0 => (0x01, FlagsRegister::from(0x00), 0xFF13, 0x00C1, 0x8403),
+            Model::Dmg => (0x01, FlagsRegister::new(enable_flags), 0x0013, 0x00D8, 0x014D),
+            Model::Mgb => (0xFF, FlagsRegister::new(enable_flags), 0x0013, 0x00D8, 0x014D),
+            Model::Sgb => (0x01, FlagsRegister::from(0x00), 0x0014, 0x0000, 0xC060),
+            Model::Sgb2 => (0xFF, FlagsRegister::from(0x00), 0x0014, 0x0000, 0xC060),
+            Model::Cgb if header.cgb == CgbSupport::None => {
+                // In compatibility mode, B holds the title checksum the
+                // boot ROM picked a palette with, for Nintendo games only
+                let b = if header.licensee_code() == "01" {
+                    header.title_checksum
+                } else {
+                    0x00
+                };
+                let hl = if b == 0x43 || b == 0x58 { 0x991A } else { 0x007C };
+
+                (0x11, FlagsRegister::from(0x80), (b as u16) << 8, 0x0008, hl)
+            }
+            Model::Cgb => (0x11, FlagsRegister::from(0x80), 0x0000, 0xFF56, 0x000D),
+        };
+
+        let mut registers = Self {
+            a,
             b: 0x00,
-            c: 0x13,
+            c: 0x00,
             d: 0x00,
-            e: 0xD8,
-            f: FlagsRegister::new(enable_flags),
-            h: 0x01,
-            l: 0x4D,
-        }
+            e: 0x00,
+            f,
+            h: 0x00,
+            l: 0x00,
+        };
+
+        registers.set_bc(bc);
+        registers.set_de(de);
+        registers.set_hl(hl);
+
+        registers
     }
 
     pub fn get_af(&self) -> u16 {
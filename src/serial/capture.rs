@@ -0,0 +1,38 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::serial::SerialDevice;
+
+// Records every byte the Game Boy sends, e.g. the output
+// of test ROMs. Nothing is sent back, like without a cable
+pub struct CaptureBuffer {
+    bytes: Rc<RefCell<Vec<u8>>>,
+}
+
+impl CaptureBuffer {
+    pub fn new() -> Self {
+        Self {
+            bytes: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    pub fn bytes(&self) -> Rc<RefCell<Vec<u8>>> {
+        // Shared with the buffer, which can still be read
+        // after it has been connected to a machine
+
+        Rc::clone(&self.bytes)
+    }
+}
+
+impl Default for CaptureBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerialDevice for CaptureBuffer {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.bytes.borrow_mut().push(byte);
+        0xFF
+    }
+}
@@ -0,0 +1,11 @@
+use crate::serial::SerialDevice;
+
+// Without a cable, the input line is pulled high and no
+// external clock ever arrives
+pub struct Disconnected;
+
+impl SerialDevice for Disconnected {
+    fn transfer(&mut self, _byte: u8) -> u8 {
+        0xFF
+    }
+}
@@ -0,0 +1,94 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::serial::SerialDevice;
+
+// State of one end of the cable
+#[derive(Default)]
+struct Port {
+    // Byte offered while waiting for the other side's clock
+    waiting: Option<u8>,
+    // Byte received from the other side's clock
+    received: Option<u8>,
+}
+
+// One end of a cable between two emulated Game Boys, which
+// are stepped by the same thread
+pub struct GameBoyLink {
+    ports: Rc<RefCell<[Port; 2]>>,
+    side: usize,
+}
+
+impl GameBoyLink {
+    pub fn pair() -> (Self, Self) {
+        let ports = Rc::new(RefCell::new([Port::default(), Port::default()]));
+
+        (
+            Self {
+                ports: Rc::clone(&ports),
+                side: 0,
+            },
+            Self { ports, side: 1 },
+        )
+    }
+}
+
+impl SerialDevice for GameBoyLink {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        // The other side only takes part if it waits for an external
+        // clock, otherwise the line reads high
+
+        let mut ports = self.ports.borrow_mut();
+        let other = &mut ports[1 - self.side];
+
+        match other.waiting.take() {
+            Some(reply) => {
+                other.received = Some(byte);
+                reply
+            }
+            None => 0xFF,
+        }
+    }
+
+    fn external_clock(&mut self, byte: u8) -> Option<u8> {
+        let mut ports = self.ports.borrow_mut();
+        let port = &mut ports[self.side];
+
+        let received = port.received.take();
+        if received.is_none() {
+            port.waiting = Some(byte);
+        }
+
+        received
+    }
+
+    fn idle(&mut self) {
+        // A transfer which was given up no longer takes part
+        self.ports.borrow_mut()[self.side].waiting = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfer_exchanges_bytes_with_waiting_side() {
+        let (mut master, mut slave) = GameBoyLink::pair();
+
+        assert_eq!(slave.external_clock(0x42), None);
+        assert_eq!(master.transfer(0x99), 0x42);
+        assert_eq!(slave.external_clock(0x42), Some(0x99));
+    }
+
+    #[test]
+    fn idle_withdraws_offered_byte() {
+        let (mut master, mut slave) = GameBoyLink::pair();
+
+        assert_eq!(slave.external_clock(0x42), None);
+        slave.idle();
+
+        assert_eq!(master.transfer(0x99), 0xFF);
+        assert_eq!(slave.external_clock(0x42), None);
+    }
+}
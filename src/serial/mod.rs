@@ -0,0 +1,189 @@
+mod capture;
+mod disconnected;
+mod link;
+mod printer;
+mod socket;
+mod stdout;
+
+pub use crate::serial::capture::CaptureBuffer;
+pub use crate::serial::disconnected::Disconnected;
+pub use crate::serial::link::GameBoyLink;
+pub use crate::serial::printer::GameBoyPrinter;
+pub use crate::serial::socket::SocketLink;
+pub use crate::serial::stdout::StdoutSink;
+
+pub const SB: u16 = 0xFF01;
+pub const SC: u16 = 0xFF02;
+
+// Bit 7 starts a transfer and stays set until it completes,
+// bit 1 selects the fast clock on CGB, bit 0 the internal clock
+const TRANSFER_ENABLE: u8 = 0b1000_0000;
+const FAST_CLOCK: u8 = 0b0000_0010;
+const INTERNAL_CLOCK: u8 = 0b0000_0001;
+const SC_UNUSED: u8 = 0b0111_1110;
+const CGB_SC_UNUSED: u8 = 0b0111_1100;
+
+// The internal clock runs at 8192 Hz, or 262144 Hz with the fast clock
+const BIT_CYCLES: u16 = 512;
+const FAST_BIT_CYCLES: u16 = 16;
+
+pub trait SerialDevice {
+    // Called when this Game Boy starts a transfer with its internal
+    // clock, returns the byte shifted in from the other side
+    fn transfer(&mut self, byte: u8) -> u8;
+
+    // Polled while this Game Boy waits for an external clock, with
+    // the byte it shifts out. Returns the received byte once the
+    // other side clocked a whole transfer
+    fn external_clock(&mut self, _byte: u8) -> Option<u8> {
+        None
+    }
+
+    // Polled while no transfer is running, so a byte offered for an
+    // external clock is withdrawn and messages of the other side are
+    // picked up without this Game Boy taking part
+    fn idle(&mut self) {}
+
+    // Called when the emulator exits, so whatever the device
+    // buffered is written out
+    fn finish(&mut self) {}
+}
+
+pub struct Serial {
+    device: Box<dyn SerialDevice>,
+    data: u8,
+    control: u8,
+    cgb_mode: bool,
+    // Byte shifted in bit by bit during an internal clock transfer
+    incoming: u8,
+    bits: u8,
+    cycles: u16,
+}
+
+impl Serial {
+    pub fn new(cgb_mode: bool) -> Self {
+        // The CGB boot ROM leaves the internal clock selected
+        let control = if cgb_mode { INTERNAL_CLOCK } else { 0 };
+
+        Self {
+            device: Box::new(Disconnected),
+            data: 0,
+            control,
+            cgb_mode,
+            incoming: 0,
+            bits: 0,
+            cycles: 0,
+        }
+    }
+
+    pub fn set_cgb_mode(&mut self, cgb_mode: bool) {
+        self.cgb_mode = cgb_mode;
+    }
+
+    pub fn connect(&mut self, device: Box<dyn SerialDevice>) {
+        self.device = device;
+    }
+
+    pub fn finish(&mut self) {
+        self.device.finish();
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            SB => self.data,
+            SC if self.cgb_mode => CGB_SC_UNUSED | self.control,
+            SC => SC_UNUSED | self.control,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            SB => self.data = value,
+            SC => {
+                let mask = if self.cgb_mode {
+                    !CGB_SC_UNUSED
+                } else {
+                    !SC_UNUSED
+                };
+                self.control = value & mask;
+
+                if self.is_internal_transfer() {
+                    self.incoming = self.device.transfer(self.data);
+                    self.bits = 0;
+                    self.cycles = 0;
+                } else {
+                    // The other side is polled on the next tick, so it
+                    // knows right away whether a transfer is awaited
+                    self.cycles = BIT_CYCLES;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn tick(&mut self, cycles: u8) -> bool {
+        // Returns whether the serial interrupt is requested, which
+        // happens once all 8 bits of a transfer have been shifted
+
+        if !self.is_internal_transfer() {
+            return self.poll_device(cycles);
+        }
+
+        let bit_cycles = if self.control & FAST_CLOCK != 0 {
+            FAST_BIT_CYCLES
+        } else {
+            BIT_CYCLES
+        };
+
+        self.cycles += cycles as u16;
+
+        while self.cycles >= bit_cycles {
+            self.cycles -= bit_cycles;
+
+            // The MSB is shifted out while the incoming bit is shifted in
+            self.data = (self.data << 1) | (self.incoming >> 7);
+            self.incoming <<= 1;
+            self.bits += 1;
+
+            if self.bits == 8 {
+                return self.finish_transfer();
+            }
+        }
+
+        false
+    }
+
+    fn poll_device(&mut self, cycles: u8) -> bool {
+        // Without an internal clock, the other side is polled
+        // once per bit period of the normal clock
+
+        self.cycles += cycles as u16;
+        if self.cycles < BIT_CYCLES {
+            return false;
+        }
+        self.cycles -= BIT_CYCLES;
+
+        if self.control & TRANSFER_ENABLE == 0 {
+            self.device.idle();
+            return false;
+        }
+
+        match self.device.external_clock(self.data) {
+            Some(byte) => {
+                self.data = byte;
+                self.finish_transfer()
+            }
+            None => false,
+        }
+    }
+
+    fn is_internal_transfer(&self) -> bool {
+        self.control & (TRANSFER_ENABLE | INTERNAL_CLOCK) == TRANSFER_ENABLE | INTERNAL_CLOCK
+    }
+
+    fn finish_transfer(&mut self) -> bool {
+        self.control &= !TRANSFER_ENABLE;
+        true
+    }
+}
@@ -0,0 +1,398 @@
+use std::path::PathBuf;
+
+use crate::png;
+use crate::serial::SerialDevice;
+
+// Every packet starts with these two bytes
+const MAGIC: [u8; 2] = [0x88, 0x33];
+
+const INIT: u8 = 0x01;
+const PRINT: u8 = 0x02;
+const DATA: u8 = 0x04;
+const STATUS: u8 = 0x0F;
+
+// Sent while the Game Boy transfers the first byte after the checksum
+const DEVICE_ID: u8 = 0x81;
+
+// Status bits, sent while the Game Boy transfers the last byte
+const CHECKSUM_ERROR: u8 = 0b0000_0001;
+const BUSY: u8 = 0b0000_0010;
+const IMAGE_DATA_FULL: u8 = 0b0000_0100;
+const UNPROCESSED_DATA: u8 = 0b0000_1000;
+
+// RLE compressed data alternates between control bytes for runs,
+// followed by the repeated byte, and for literal bytes
+const RUN: u8 = 0b1000_0000;
+const RUN_LENGTH: u8 = 0b0111_1111;
+
+// The printer buffers up to 9 DATA packets of two 160 pixel wide tile rows
+const PRINT_WIDTH: usize = 160;
+const TILES_PER_ROW: usize = PRINT_WIDTH / 8;
+const TILE_SIZE: usize = 16;
+const MAX_IMAGE_DATA: usize = TILES_PER_ROW * TILE_SIZE * 2 * 9;
+
+// Margins of the PRINT command, the lower nibble is fed after printing
+const MARGIN_AFTER: u8 = 0b0000_1111;
+
+// Shades from white to black
+const SHADES: [u8; 4] = [0xFF, 0xAA, 0x55, 0x00];
+const DEFAULT_PALETTE: u8 = 0xE4;
+
+// Number of packets during which the printer reports to be busy
+const BUSY_PACKETS: u8 = 4;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum State {
+    MagicFirst,
+    MagicSecond,
+    Command,
+    Compression,
+    LengthLow,
+    LengthHigh,
+    Data,
+    ChecksumLow,
+    ChecksumHigh,
+    DeviceId,
+    Status,
+}
+
+pub struct GameBoyPrinter {
+    // Every print job is written to its own PNG file in this directory
+    output_dir: PathBuf,
+    state: State,
+    command: u8,
+    compressed: bool,
+    length: u16,
+    data: Vec<u8>,
+    checksum: u16,
+    // Decompressed tile data received since the last print
+    image_data: Vec<u8>,
+    // Grayscale pixels of all strips printed without a margin in between
+    job: Vec<u8>,
+    status: u8,
+    busy_packets: u8,
+    jobs: usize,
+}
+
+impl GameBoyPrinter {
+    pub fn new(output_dir: PathBuf) -> Self {
+        Self {
+            output_dir,
+            state: State::MagicFirst,
+            command: 0,
+            compressed: false,
+            length: 0,
+            data: Vec::new(),
+            checksum: 0,
+            image_data: Vec::with_capacity(MAX_IMAGE_DATA),
+            job: Vec::new(),
+            status: 0,
+            busy_packets: 0,
+            jobs: 0,
+        }
+    }
+
+    fn receive(&mut self, byte: u8) -> State {
+        // Returns the next state of the packet
+
+        match self.state {
+            State::MagicFirst if byte == MAGIC[0] => State::MagicSecond,
+            State::MagicFirst => State::MagicFirst,
+            State::MagicSecond if byte == MAGIC[1] => State::Command,
+            State::MagicSecond => State::MagicFirst,
+            State::Command => {
+                self.command = byte;
+                State::Compression
+            }
+            State::Compression => {
+                self.compressed = byte & 0b1 != 0;
+                State::LengthLow
+            }
+            State::LengthLow => {
+                self.length = byte as u16;
+                State::LengthHigh
+            }
+            State::LengthHigh => {
+                self.length |= (byte as u16) << 8;
+                self.data.clear();
+
+                if self.length == 0 {
+                    State::ChecksumLow
+                } else {
+                    State::Data
+                }
+            }
+            State::Data => {
+                self.data.push(byte);
+
+                if self.data.len() == self.length as usize {
+                    State::ChecksumLow
+                } else {
+                    State::Data
+                }
+            }
+            State::ChecksumLow => {
+                self.checksum = byte as u16;
+                State::ChecksumHigh
+            }
+            State::ChecksumHigh => {
+                self.checksum |= (byte as u16) << 8;
+                self.execute();
+                State::DeviceId
+            }
+            State::DeviceId => State::Status,
+            State::Status => State::MagicFirst,
+        }
+    }
+
+    fn execute(&mut self) {
+        // The checksum is the sum of all bytes from the command
+        // up to the end of the data
+        let checksum = [self.command, self.compressed as u8]
+            .iter()
+            .chain(&self.length.to_le_bytes())
+            .chain(&self.data)
+            .fold(0u16, |sum, &byte| sum.wrapping_add(byte as u16));
+
+        if checksum != self.checksum {
+            self.status |= CHECKSUM_ERROR;
+            return;
+        }
+        self.status &= !CHECKSUM_ERROR;
+
+        if self.busy_packets > 0 {
+            self.busy_packets -= 1;
+            if self.busy_packets == 0 {
+                self.status &= !BUSY;
+            }
+        }
+
+        match self.command {
+            INIT => {
+                self.image_data.clear();
+                self.status = 0;
+                self.busy_packets = 0;
+            }
+            PRINT if self.data.len() >= 4 => self.print(self.data[0], self.data[1], self.data[2]),
+            // An empty DATA packet ends the image data
+            DATA if self.data.is_empty() => self.status |= IMAGE_DATA_FULL,
+            DATA => {
+                let data = if self.compressed {
+                    decompress(&self.data)
+                } else {
+                    self.data.clone()
+                };
+
+                let free = MAX_IMAGE_DATA - self.image_data.len();
+                self.image_data.extend(data.into_iter().take(free));
+                self.status |= UNPROCESSED_DATA;
+            }
+            STATUS => {}
+            _ => {}
+        }
+    }
+
+    fn print(&mut self, sheets: u8, margins: u8, palette: u8) {
+        // Prints the buffered tile rows as a strip, the print job ends
+        // once paper is fed after it. The exposure is not emulated
+
+        self.status |= BUSY;
+        self.busy_packets = BUSY_PACKETS;
+
+        // No sheets only feeds paper, the buffered rows are kept
+        if sheets == 0 {
+            if margins & MARGIN_AFTER != 0 {
+                self.finish_job();
+            }
+            return;
+        }
+
+        // Some games send 0, which the printer treats like 0xE4
+        let palette = if palette == 0 {
+            DEFAULT_PALETTE
+        } else {
+            palette
+        };
+
+        let row_size = TILES_PER_ROW * TILE_SIZE;
+        for tile_row in self.image_data.chunks_exact(row_size) {
+            for y in 0..8 {
+                for x in 0..PRINT_WIDTH {
+                    let address = (x / 8) * TILE_SIZE + y * 2;
+                    let bit = 7 - (x % 8);
+                    let low = (tile_row[address] >> bit) & 0b1;
+                    let high = (tile_row[address + 1] >> bit) & 0b1;
+                    let color = (high << 1) | low;
+
+                    let shade = (palette >> (color * 2)) & 0b11;
+                    self.job.push(SHADES[shade as usize]);
+                }
+            }
+        }
+
+        self.image_data.clear();
+        self.status &= !(UNPROCESSED_DATA | IMAGE_DATA_FULL);
+
+        if margins & MARGIN_AFTER != 0 {
+            self.finish_job();
+        }
+    }
+
+    fn finish_job(&mut self) {
+        if self.job.is_empty() {
+            return;
+        }
+
+        // Prints of earlier sessions in the same directory are kept
+        let path = loop {
+            self.jobs += 1;
+            let path = self.output_dir.join(format!("print_{:03}.png", self.jobs));
+
+            if !path.exists() {
+                break path;
+            }
+        };
+        let height = self.job.len() / PRINT_WIDTH;
+
+        if let Err(error) = png::write_grayscale(&path, PRINT_WIDTH, height, &self.job) {
+            eprintln!("Warning: Can't write {}: {}", path.display(), error);
+        }
+
+        self.job.clear();
+    }
+}
+
+impl SerialDevice for GameBoyPrinter {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        // The printer answers with 0x00, except for the last two bytes
+        // of a packet, which are dummy bytes sent by the Game Boy
+
+        let reply = match self.state {
+            State::DeviceId => DEVICE_ID,
+            State::Status => self.status,
+            _ => 0x00,
+        };
+
+        self.state = self.receive(byte);
+
+        reply
+    }
+
+    fn finish(&mut self) {
+        // A job which never got a margin after it is written as well
+        self.finish_job();
+    }
+}
+
+impl Drop for GameBoyPrinter {
+    fn drop(&mut self) {
+        self.finish_job();
+    }
+}
+
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(MAX_IMAGE_DATA);
+    let mut bytes = data.iter();
+
+    while let Some(&control) = bytes.next() {
+        if control & RUN != 0 {
+            // Runs repeat the next byte 2-129 times
+            let length = (control & RUN_LENGTH) as usize + 2;
+            if let Some(&byte) = bytes.next() {
+                output.extend(std::iter::repeat_n(byte, length));
+            }
+        } else {
+            // Followed by 1-128 literal bytes
+            let length = control as usize + 1;
+            output.extend(bytes.by_ref().take(length));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_TILE_ROWS: usize = TILES_PER_ROW * TILE_SIZE * 2;
+
+    fn output_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("gemboi-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    fn send(printer: &mut GameBoyPrinter, command: u8, data: &[u8], checksum_offset: u16) -> u8 {
+        // Returns the status sent during the last byte
+
+        let mut packet = vec![MAGIC[0], MAGIC[1], command, 0x00];
+        packet.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        packet.extend_from_slice(data);
+
+        let checksum = packet[2..]
+            .iter()
+            .fold(checksum_offset, |sum, &byte| sum.wrapping_add(byte as u16));
+        packet.extend_from_slice(&checksum.to_le_bytes());
+
+        for byte in packet {
+            assert_eq!(printer.transfer(byte), 0x00);
+        }
+
+        assert_eq!(printer.transfer(0x00), DEVICE_ID);
+        printer.transfer(0x00)
+    }
+
+    #[test]
+    fn bad_checksum_is_reported() {
+        let mut printer = GameBoyPrinter::new(output_dir("checksum"));
+
+        assert_eq!(send(&mut printer, STATUS, &[], 1), CHECKSUM_ERROR);
+        assert_eq!(send(&mut printer, STATUS, &[], 0), 0);
+    }
+
+    #[test]
+    fn decompress_expands_runs_and_literals() {
+        let data = [0x81, 0xAA, 0x01, 0x12, 0x34];
+
+        assert_eq!(decompress(&data), [0xAA, 0xAA, 0xAA, 0x12, 0x34]);
+    }
+
+    #[test]
+    fn print_picks_next_unused_file() {
+        let dir = output_dir("print");
+        std::fs::write(dir.join("print_001.png"), b"earlier").unwrap();
+
+        let mut printer = GameBoyPrinter::new(dir.clone());
+        send(&mut printer, INIT, &[], 0);
+        send(&mut printer, DATA, &[0xFF; TWO_TILE_ROWS], 0);
+        send(&mut printer, PRINT, &[0x01, 0x01, 0xE4, 0x40], 0);
+
+        assert_eq!(
+            std::fs::read(dir.join("print_001.png")).unwrap(),
+            b"earlier"
+        );
+
+        let png = std::fs::read(dir.join("print_002.png")).unwrap();
+        assert_eq!(&png[16..24], &[0, 0, 0, 160, 0, 0, 0, 16]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn print_without_sheets_only_feeds_paper() {
+        let dir = output_dir("feed");
+
+        let mut printer = GameBoyPrinter::new(dir.clone());
+        send(&mut printer, INIT, &[], 0);
+        send(&mut printer, DATA, &[0xFF; TWO_TILE_ROWS], 0);
+        let status = send(&mut printer, PRINT, &[0x00, 0x01, 0xE4, 0x40], 0);
+
+        assert_ne!(status & UNPROCESSED_DATA, 0);
+        assert!(!dir.join("print_001.png").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,429 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::Path;
+use std::time::Duration;
+
+use crate::serial::SerialDevice;
+
+// Sent by both sides after connecting, followed by the protocol version
+const MAGIC: &[u8; 4] = b"GBLK";
+const VERSION: u8 = 2;
+
+// Every message is a kind byte, a sequence number and a data byte.
+// The side using its internal clock sends TRANSFER and blocks until
+// the other side answers with REPLY, holding its SB. A transfer is
+// only answered once the other side waits for an external clock, so
+// the result doesn't depend on when the sockets are polled. When both
+// sides use their internal clock, both transfers are answered with
+// 0xFF. The reply is then confirmed with ACCEPT, or the transfer is
+// given up with CANCEL if no reply arrived in time. The answering side
+// only completes its transfer on ACCEPT, so a reply which crossed a
+// CANCEL doesn't leave the two sides with different results
+const TRANSFER: u8 = 0x01;
+const REPLY: u8 = 0x02;
+const CANCEL: u8 = 0x03;
+const ACCEPT: u8 = 0x04;
+const MESSAGE_SIZE: usize = 3;
+
+// Long enough for the other side to start waiting within a few frames
+const TRANSFER_TIMEOUT: Duration = Duration::from_millis(50);
+
+trait Socket: Read + Write {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl Socket for TcpStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        TcpStream::set_nonblocking(self, nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl Socket for UnixStream {
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        UnixStream::set_nonblocking(self, nonblocking)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+// Link cable to another emulator instance over a TCP
+// or Unix domain socket, usually on the same machine
+pub struct SocketLink {
+    // None once the connection was lost, which then
+    // behaves like a disconnected cable
+    socket: Option<Box<dyn Socket>>,
+    // Bytes of a message which was only partially received
+    pending: Vec<u8>,
+    // Sequence number of the next transfer sent
+    sequence: u8,
+    // Sequence number and byte of a transfer from the other
+    // side, which is answered once an external clock is awaited
+    incoming: Option<(u8, u8)>,
+}
+
+impl SocketLink {
+    pub fn listen_tcp(address: impl ToSocketAddrs) -> io::Result<Self> {
+        // Waits for the other instance to connect
+        let (stream, _) = TcpListener::bind(address)?.accept()?;
+        stream.set_nodelay(true)?;
+
+        Self::handshake(Box::new(stream))
+    }
+
+    pub fn connect_tcp(address: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+
+        Self::handshake(Box::new(stream))
+    }
+
+    #[cfg(unix)]
+    pub fn listen_unix(path: &Path) -> io::Result<Self> {
+        // A stale socket file of an earlier session is replaced,
+        // but any other file is left alone
+        match std::fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)?,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "Error: The link cable path exists and is not a socket",
+                ));
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+
+        let (stream, _) = UnixListener::bind(path)?.accept()?;
+
+        Self::handshake(Box::new(stream))
+    }
+
+    #[cfg(unix)]
+    pub fn connect_unix(path: &Path) -> io::Result<Self> {
+        Self::handshake(Box::new(UnixStream::connect(path)?))
+    }
+
+    fn handshake(mut socket: Box<dyn Socket>) -> io::Result<Self> {
+        let mut hello = [0; 5];
+        hello[..4].copy_from_slice(MAGIC);
+        hello[4] = VERSION;
+        socket.write_all(&hello)?;
+
+        let mut peer = [0; 5];
+        socket.read_exact(&mut peer)?;
+
+        if &peer[..4] != MAGIC || peer[4] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Error: The other side is not a compatible link cable",
+            ));
+        }
+
+        // The timeout only applies to blocking reads
+        socket.set_read_timeout(Some(TRANSFER_TIMEOUT))?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket: Some(socket),
+            pending: Vec::with_capacity(MESSAGE_SIZE),
+            sequence: 0,
+            incoming: None,
+        })
+    }
+
+    fn send(&mut self, kind: u8, sequence: u8, byte: u8) {
+        let result = match self.socket {
+            Some(ref mut socket) => socket.write_all(&[kind, sequence, byte]),
+            None => return,
+        };
+
+        if let Err(error) = result {
+            self.disconnect(error);
+        }
+    }
+
+    fn try_receive(&mut self) -> Option<(u8, u8, u8)> {
+        // Returns a message if one arrived, without blocking
+
+        let socket = self.socket.as_mut()?;
+        let mut buffer = [0; MESSAGE_SIZE];
+
+        while self.pending.len() < MESSAGE_SIZE {
+            let wanted = MESSAGE_SIZE - self.pending.len();
+
+            match socket.read(&mut buffer[..wanted]) {
+                Ok(0) => {
+                    let error = io::Error::from(io::ErrorKind::UnexpectedEof);
+                    self.disconnect(error);
+                    return None;
+                }
+                Ok(read) => self.pending.extend_from_slice(&buffer[..read]),
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    return None
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.disconnect(error);
+                    return None;
+                }
+            }
+        }
+
+        let message = (self.pending[0], self.pending[1], self.pending[2]);
+        self.pending.clear();
+
+        Some(message)
+    }
+
+    fn receive(&mut self) -> Option<(u8, u8, u8)> {
+        // Blocks until a message arrives, the connection is
+        // lost or no message arrived within TRANSFER_TIMEOUT
+
+        self.set_nonblocking(false);
+        let message = self.try_receive();
+        self.set_nonblocking(true);
+
+        message
+    }
+
+    fn set_nonblocking(&mut self, nonblocking: bool) {
+        let result = match self.socket {
+            Some(ref socket) => socket.set_nonblocking(nonblocking),
+            None => return,
+        };
+
+        if let Err(error) = result {
+            self.disconnect(error);
+        }
+    }
+
+    fn poll(&mut self) {
+        // Takes note of every message which arrived, without blocking
+
+        while let Some((kind, sequence, value)) = self.try_receive() {
+            match kind {
+                // An older transfer was already given up by the other side
+                TRANSFER => self.incoming = Some((sequence, value)),
+                CANCEL if self.incoming.map(|(pending, _)| pending) == Some(sequence) => {
+                    self.incoming = None;
+                }
+                // Replies to transfers which were given up, and verdicts
+                // on transfers which were answered with 0xFF
+                _ => {}
+            }
+        }
+    }
+
+    fn disconnect(&mut self, error: io::Error) {
+        eprintln!("Warning: Link cable disconnected: {}", error);
+        self.socket = None;
+        self.incoming = None;
+    }
+}
+
+impl SerialDevice for SocketLink {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        // A transfer of the other side is answered with 0xFF,
+        // as both sides use their internal clock
+        self.poll();
+        if let Some((sequence, _)) = self.incoming.take() {
+            self.send(REPLY, sequence, 0xFF);
+        }
+
+        let sequence = self.sequence;
+        self.sequence = self.sequence.wrapping_add(1);
+        self.send(TRANSFER, sequence, byte);
+
+        while let Some((kind, received, value)) = self.receive() {
+            match kind {
+                REPLY if received == sequence => {
+                    self.send(ACCEPT, sequence, 0);
+                    return value;
+                }
+                TRANSFER => self.send(REPLY, received, 0xFF),
+                _ => {}
+            }
+        }
+
+        // Nobody answered, like without a cable
+        self.send(CANCEL, sequence, 0);
+
+        0xFF
+    }
+
+    fn external_clock(&mut self, byte: u8) -> Option<u8> {
+        self.poll();
+
+        let (sequence, value) = self.incoming.take()?;
+        self.send(REPLY, sequence, byte);
+
+        // The other side sends its verdict right after the reply
+        // arrives, or already gave the transfer up before that
+        loop {
+            match self.receive() {
+                Some((ACCEPT, received, _)) if received == sequence => return Some(value),
+                Some((CANCEL, received, _)) if received == sequence => return None,
+                Some((TRANSFER, received, value)) => self.incoming = Some((received, value)),
+                Some(_) => {}
+                None if self.socket.is_none() => return None,
+                None => {}
+            }
+        }
+    }
+
+    fn idle(&mut self) {
+        // A transfer of the other side stays pending until
+        // this side waits for an external clock or it is given up
+        self.poll();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("gemboi-{}-{}.sock", name, std::process::id()))
+    }
+
+    fn connect(path: &Path) -> SocketLink {
+        loop {
+            match SocketLink::connect_unix(path) {
+                Ok(link) => return link,
+                Err(_) => thread::sleep(Duration::from_millis(1)),
+            }
+        }
+    }
+
+    #[test]
+    fn listen_unix_keeps_other_files() {
+        let path = socket_path("file");
+        std::fs::write(&path, b"save").unwrap();
+
+        let error = SocketLink::listen_unix(&path).err().unwrap();
+
+        assert_eq!(error.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&path).unwrap(), b"save");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn transfer_waits_for_external_clock() {
+        let path = socket_path("wait");
+        let listen_path = path.clone();
+
+        let slave = thread::spawn(move || {
+            let mut link = SocketLink::listen_unix(&listen_path).unwrap();
+            link.idle();
+
+            loop {
+                if let Some(byte) = link.external_clock(0x42) {
+                    return byte;
+                }
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+
+        let mut master = connect(&path);
+
+        assert_eq!(master.transfer(0x99), 0x42);
+        assert_eq!(slave.join().unwrap(), 0x99);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    fn connect_raw(path: &Path) -> UnixStream {
+        // The other side without a SocketLink, so the order of its
+        // messages is fixed by the test
+        let mut stream = loop {
+            match UnixStream::connect(path) {
+                Ok(stream) => break stream,
+                Err(_) => thread::sleep(Duration::from_millis(1)),
+            }
+        };
+        stream.write_all(MAGIC).unwrap();
+        stream.write_all(&[VERSION]).unwrap();
+        stream.read_exact(&mut [0; 5]).unwrap();
+
+        stream
+    }
+
+    fn await_transfer(link: &mut SocketLink) {
+        while link.incoming.is_none() {
+            link.idle();
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn transfer_gives_up_without_external_clock() {
+        let path = socket_path("cancel");
+        let listen_path = path.clone();
+        let (done_sender, done) = mpsc::channel();
+
+        let slave = thread::spawn(move || {
+            let mut link = SocketLink::listen_unix(&listen_path).unwrap();
+            done.recv().unwrap();
+            link.external_clock(0x42)
+        });
+
+        let mut master = connect(&path);
+
+        assert_eq!(master.transfer(0x99), 0xFF);
+        done_sender.send(()).unwrap();
+        assert_eq!(slave.join().unwrap(), None);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn reply_only_completes_once_accepted() {
+        let path = socket_path("accept");
+        let connect_path = path.clone();
+
+        let master = thread::spawn(move || {
+            let mut stream = connect_raw(&connect_path);
+            let mut replies = [[0; MESSAGE_SIZE]; 2];
+
+            // The first reply crossed a CANCEL, the second is accepted
+            stream.write_all(&[TRANSFER, 0, 0x99]).unwrap();
+            stream.read_exact(&mut replies[0]).unwrap();
+            stream.write_all(&[CANCEL, 0, 0]).unwrap();
+
+            stream.write_all(&[TRANSFER, 1, 0x77]).unwrap();
+            stream.read_exact(&mut replies[1]).unwrap();
+            stream.write_all(&[ACCEPT, 1, 0]).unwrap();
+
+            replies
+        });
+
+        let mut slave = SocketLink::listen_unix(&path).unwrap();
+
+        await_transfer(&mut slave);
+        assert_eq!(slave.external_clock(0x42), None);
+        await_transfer(&mut slave);
+        assert_eq!(slave.external_clock(0x43), Some(0x77));
+
+        assert_eq!(master.join().unwrap(), [[REPLY, 0, 0x42], [REPLY, 1, 0x43]]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,15 @@
+use std::io::{self, Write};
+
+use crate::serial::SerialDevice;
+
+// Prints every byte the Game Boy sends as a character, which
+// is how test ROMs like Blargg's report their results
+pub struct StdoutSink;
+
+impl SerialDevice for StdoutSink {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        print!("{}", byte as char);
+        io::stdout().flush().ok();
+        0xFF
+    }
+}
@@ -0,0 +1,110 @@
+use super::{SGB_SCREEN_HEIGHT, SGB_SCREEN_WIDTH};
+
+// SNES 4bpp tiles, CHR_TRN sends either half of them
+const TILE_SIZE: usize = 32;
+const TILE_COUNT: usize = 256;
+const HALF_SIZE: usize = TILE_SIZE * TILE_COUNT / 2;
+
+// PCT_TRN sends a 32x32 tile map followed by palettes 4-7
+const MAP_WIDTH: usize = 32;
+const MAP_ENTRIES: usize = MAP_WIDTH * MAP_WIDTH;
+const PALETTE_OFFSET: usize = MAP_ENTRIES * 2;
+const PALETTE_COUNT: usize = 4;
+const PALETTE_COLORS: usize = 16;
+const FIRST_PALETTE: u16 = 4;
+
+// Tile map entry bits
+const MAP_TILE: u16 = 0b0000_0000_1111_1111;
+const MAP_PALETTE: u16 = 0b0001_1100_0000_0000;
+const MAP_X_FLIP: u16 = 0b0100_0000_0000_0000;
+const MAP_Y_FLIP: u16 = 0b1000_0000_0000_0000;
+
+pub struct Border {
+    tiles: [u8; TILE_SIZE * TILE_COUNT],
+    map: [u16; MAP_ENTRIES],
+    palettes: [[u16; PALETTE_COLORS]; PALETTE_COUNT],
+}
+
+impl Border {
+    pub fn new() -> Self {
+        // Without a transferred border, only the backdrop color is shown
+        Self {
+            tiles: [0; TILE_SIZE * TILE_COUNT],
+            map: [0; MAP_ENTRIES],
+            palettes: [[0; PALETTE_COLORS]; PALETTE_COUNT],
+        }
+    }
+
+    pub fn load_tiles(&mut self, data: &[u8], upper_half: bool) {
+        let offset = if upper_half { HALF_SIZE } else { 0 };
+        self.tiles[offset..offset + HALF_SIZE].copy_from_slice(&data[..HALF_SIZE]);
+    }
+
+    pub fn load_map(&mut self, data: &[u8]) {
+        for (entry, bytes) in self.map.iter_mut().zip(data.chunks_exact(2)) {
+            *entry = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+
+        let palette_data = &data[PALETTE_OFFSET..];
+        for (color, bytes) in self
+            .palettes
+            .as_flattened_mut()
+            .iter_mut()
+            .zip(palette_data.chunks_exact(2))
+        {
+            *color = u16::from_le_bytes([bytes[0], bytes[1]]) & 0x7FFF;
+        }
+    }
+
+    pub fn render(&self, frame_buffer: &mut [u16]) {
+        // Draws over the game screen, color 0 of every tile is transparent
+
+        for y in 0..SGB_SCREEN_HEIGHT {
+            for x in 0..SGB_SCREEN_WIDTH {
+                let entry = self.map[(y / 8) * MAP_WIDTH + x / 8];
+
+                let tile_x = if entry & MAP_X_FLIP != 0 {
+                    7 - x % 8
+                } else {
+                    x % 8
+                };
+                let tile_y = if entry & MAP_Y_FLIP != 0 {
+                    7 - y % 8
+                } else {
+                    y % 8
+                };
+
+                let color = self.tile_pixel((entry & MAP_TILE) as usize, tile_x, tile_y);
+                if color == 0 {
+                    continue;
+                }
+
+                // Only palettes 4-7 can be used for the border
+                let palette = ((entry & MAP_PALETTE) >> 10).wrapping_sub(FIRST_PALETTE) as usize;
+                if let Some(palette) = self.palettes.get(palette) {
+                    frame_buffer[y * SGB_SCREEN_WIDTH + x] = palette[color as usize];
+                }
+            }
+        }
+    }
+
+    fn tile_pixel(&self, tile: usize, x: usize, y: usize) -> u8 {
+        // Bitplanes 0 and 1 are interleaved in the first 16 bytes
+        // of the tile, bitplanes 2 and 3 in the last 16 bytes
+
+        let address = tile * TILE_SIZE + y * 2;
+        let bit = 7 - x;
+
+        [
+            self.tiles[address],
+            self.tiles[address + 1],
+            self.tiles[address + 16],
+            self.tiles[address + 17],
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |color, (plane, byte)| {
+            color | (((byte >> bit) & 0b1) << plane)
+        })
+    }
+}
@@ -0,0 +1,490 @@
+mod border;
+mod packet;
+
+use crate::gpu::{Gpu, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::sgb::border::Border;
+use crate::sgb::packet::PacketReceiver;
+
+pub const SGB_SCREEN_WIDTH: usize = 256;
+pub const SGB_SCREEN_HEIGHT: usize = 224;
+
+// The game screen is centered within the border
+const SCREEN_X: usize = 48;
+const SCREEN_Y: usize = 40;
+
+// Palettes are assigned to 8x8 areas of the game screen
+const ATTRIBUTE_WIDTH: usize = SCREEN_WIDTH / 8;
+const ATTRIBUTE_HEIGHT: usize = SCREEN_HEIGHT / 8;
+const ATTRIBUTE_COUNT: usize = ATTRIBUTE_WIDTH * ATTRIBUTE_HEIGHT;
+
+// VRAM transfers send 4 KiB through the next frame
+const SYSTEM_PALETTES: usize = 512;
+const ATTRIBUTE_FILES: usize = 45;
+const ATTRIBUTE_FILE_SIZE: usize = ATTRIBUTE_COUNT / 4;
+
+const PAL01: u8 = 0x00;
+const PAL23: u8 = 0x01;
+const PAL03: u8 = 0x02;
+const PAL12: u8 = 0x03;
+const ATTR_BLK: u8 = 0x04;
+const ATTR_LIN: u8 = 0x05;
+const ATTR_CHR: u8 = 0x07;
+const PAL_SET: u8 = 0x0A;
+const PAL_TRN: u8 = 0x0B;
+const MLT_REQ: u8 = 0x11;
+const CHR_TRN: u8 = 0x13;
+const PCT_TRN: u8 = 0x14;
+const ATTR_TRN: u8 = 0x15;
+const ATTR_SET: u8 = 0x16;
+const MASK_EN: u8 = 0x17;
+
+// ATTR_BLK control code bits
+const BLOCK_INSIDE: u8 = 0b001;
+const BLOCK_LINE: u8 = 0b010;
+const BLOCK_OUTSIDE: u8 = 0b100;
+
+// ATTR_LIN data set bits
+const LINE_NUMBER: u8 = 0b0001_1111;
+const LINE_HORIZONTAL: u8 = 0b1000_0000;
+
+// PAL_SET and ATTR_SET flags
+const APPLY_ATTRIBUTE_FILE: u8 = 0b1000_0000;
+const CANCEL_MASK: u8 = 0b0100_0000;
+const ATTRIBUTE_FILE_INDEX: u8 = 0b0011_1111;
+
+// Colors the SGB boot ROM sets up for all palettes
+const DEFAULT_PALETTE: [u16; 4] = [0x67BF, 0x265B, 0x10B5, 0x2866];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Transfer {
+    // CHR_TRN sends the lower or upper 128 border tiles
+    BorderTiles(bool),
+    BorderMap,
+    SystemPalettes,
+    AttributeFiles,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Mask {
+    None,
+    // Keeps showing the last frame
+    Freeze,
+    Black,
+    // Fills the screen with color 0 of palette 0
+    Color0,
+}
+
+pub struct Sgb {
+    receiver: PacketReceiver,
+    palettes: [[u16; 4]; 4],
+    system_palettes: [[u16; 4]; SYSTEM_PALETTES],
+    attribute_files: [[u8; ATTRIBUTE_FILE_SIZE]; ATTRIBUTE_FILES],
+    // Palette number of every 8x8 area of the game screen
+    attributes: [u8; ATTRIBUTE_COUNT],
+    border: Border,
+    pending_transfer: Option<Transfer>,
+    mask: Mask,
+    players: u8,
+    screen: [u16; SCREEN_WIDTH * SCREEN_HEIGHT],
+    // RGB555 colors of the game screen surrounded by the border
+    pub frame_buffer: [u16; SGB_SCREEN_WIDTH * SGB_SCREEN_HEIGHT],
+}
+
+impl Sgb {
+    pub fn new() -> Self {
+        Self {
+            receiver: PacketReceiver::new(),
+            palettes: [DEFAULT_PALETTE; 4],
+            system_palettes: [[0; 4]; SYSTEM_PALETTES],
+            attribute_files: [[0; ATTRIBUTE_FILE_SIZE]; ATTRIBUTE_FILES],
+            attributes: [0; ATTRIBUTE_COUNT],
+            border: Border::new(),
+            pending_transfer: None,
+            mask: Mask::None,
+            players: 1,
+            screen: [DEFAULT_PALETTE[0]; SCREEN_WIDTH * SCREEN_HEIGHT],
+            frame_buffer: [DEFAULT_PALETTE[0]; SGB_SCREEN_WIDTH * SGB_SCREEN_HEIGHT],
+        }
+    }
+
+    pub fn write_joypad(&mut self, value: u8) {
+        if let Some(data) = self.receiver.write(value) {
+            self.execute(&data);
+        }
+    }
+
+    pub fn players(&self) -> u8 {
+        // Number of joypads polled through the joypad register
+        self.players
+    }
+
+    pub fn finish_frame(&mut self, gpu: &Gpu) {
+        // Called at the start of VBlank. VRAM transfers pick up
+        // the frame which was displayed after the command
+
+        if let Some(transfer) = self.pending_transfer.take() {
+            self.transfer(transfer, &gpu.screen_tile_data());
+        }
+
+        match self.mask {
+            Mask::None => {
+                for (index, &shade) in gpu.shades().iter().enumerate() {
+                    let x = index % SCREEN_WIDTH;
+                    let y = index / SCREEN_WIDTH;
+                    let palette = self.attributes[(y / 8) * ATTRIBUTE_WIDTH + x / 8];
+
+                    self.screen[index] = self.palettes[palette as usize][shade as usize];
+                }
+            }
+            Mask::Freeze => {}
+            Mask::Black => self.screen.fill(0x0000),
+            Mask::Color0 => self.screen.fill(self.palettes[0][0]),
+        }
+
+        self.frame_buffer.fill(self.palettes[0][0]);
+
+        for (y, line) in self.screen.chunks_exact(SCREEN_WIDTH).enumerate() {
+            let offset = (SCREEN_Y + y) * SGB_SCREEN_WIDTH + SCREEN_X;
+            self.frame_buffer[offset..offset + SCREEN_WIDTH].copy_from_slice(line);
+        }
+
+        self.border.render(&mut self.frame_buffer);
+    }
+
+    fn execute(&mut self, data: &[u8]) {
+        // Sound, SNES code and the remaining commands are ignored
+
+        match data[0] >> 3 {
+            PAL01 => self.set_palettes(0, 1, data),
+            PAL23 => self.set_palettes(2, 3, data),
+            PAL03 => self.set_palettes(0, 3, data),
+            PAL12 => self.set_palettes(1, 2, data),
+            ATTR_BLK => self.attribute_blocks(data),
+            ATTR_LIN => self.attribute_lines(data),
+            ATTR_CHR => self.attribute_characters(data),
+            PAL_SET => self.set_system_palettes(data),
+            PAL_TRN => self.pending_transfer = Some(Transfer::SystemPalettes),
+            MLT_REQ => {
+                self.players = match data[1] & 0b11 {
+                    1 => 2,
+                    3 => 4,
+                    _ => 1,
+                };
+            }
+            CHR_TRN => self.pending_transfer = Some(Transfer::BorderTiles(data[1] & 0b1 != 0)),
+            PCT_TRN => self.pending_transfer = Some(Transfer::BorderMap),
+            ATTR_TRN => self.pending_transfer = Some(Transfer::AttributeFiles),
+            ATTR_SET => {
+                self.apply_attribute_file(data[1] & ATTRIBUTE_FILE_INDEX);
+                if data[1] & CANCEL_MASK != 0 {
+                    self.mask = Mask::None;
+                }
+            }
+            MASK_EN => {
+                self.mask = match data[1] & 0b11 {
+                    1 => Mask::Freeze,
+                    2 => Mask::Black,
+                    3 => Mask::Color0,
+                    _ => Mask::None,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn set_palettes(&mut self, first: usize, second: usize, data: &[u8]) {
+        // Color 0 is shared by all palettes, followed by
+        // colors 1-3 of both palettes
+
+        let colors: Vec<u16> = data[1..15]
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) & 0x7FFF)
+            .collect();
+
+        for palette in self.palettes.iter_mut() {
+            palette[0] = colors[0];
+        }
+
+        self.palettes[first][1..].copy_from_slice(&colors[1..4]);
+        self.palettes[second][1..].copy_from_slice(&colors[4..7]);
+    }
+
+    fn set_system_palettes(&mut self, data: &[u8]) {
+        // Selects four of the palettes sent by PAL_TRN
+
+        for (palette, bytes) in self.palettes.iter_mut().zip(data[1..9].chunks_exact(2)) {
+            let index = u16::from_le_bytes([bytes[0], bytes[1]]) as usize % SYSTEM_PALETTES;
+            *palette = self.system_palettes[index];
+        }
+
+        let color0 = self.palettes[0][0];
+        for palette in self.palettes.iter_mut() {
+            palette[0] = color0;
+        }
+
+        let flags = data[9];
+        if flags & APPLY_ATTRIBUTE_FILE != 0 {
+            self.apply_attribute_file(flags & ATTRIBUTE_FILE_INDEX);
+        }
+        if flags & CANCEL_MASK != 0 {
+            self.mask = Mask::None;
+        }
+    }
+
+    fn attribute_blocks(&mut self, data: &[u8]) {
+        // Every data set assigns palettes to the inside, the
+        // surrounding line and the outside of a rectangle
+
+        let sets = data[1] as usize;
+
+        for set in data[2..].chunks_exact(6).take(sets) {
+            let inside = set[1] & 0b11;
+            let outside = (set[1] >> 4) & 0b11;
+
+            // Changing only the inside or the outside changes
+            // the surrounding line to the same palette
+            let (control, line) = match set[0] & 0b111 {
+                BLOCK_INSIDE => (BLOCK_INSIDE | BLOCK_LINE, inside),
+                BLOCK_OUTSIDE => (BLOCK_OUTSIDE | BLOCK_LINE, outside),
+                control => (control, (set[1] >> 2) & 0b11),
+            };
+
+            let (x1, y1, x2, y2) = (
+                set[2] as usize & 0x1F,
+                set[3] as usize & 0x1F,
+                set[4] as usize & 0x1F,
+                set[5] as usize & 0x1F,
+            );
+
+            for (index, attribute) in self.attributes.iter_mut().enumerate() {
+                let x = index % ATTRIBUTE_WIDTH;
+                let y = index / ATTRIBUTE_WIDTH;
+
+                let (region, palette) = if x > x1 && x < x2 && y > y1 && y < y2 {
+                    (BLOCK_INSIDE, inside)
+                } else if (x1..=x2).contains(&x) && (y1..=y2).contains(&y) {
+                    (BLOCK_LINE, line)
+                } else {
+                    (BLOCK_OUTSIDE, outside)
+                };
+
+                if control & region != 0 {
+                    *attribute = palette;
+                }
+            }
+        }
+    }
+
+    fn attribute_lines(&mut self, data: &[u8]) {
+        // Every data byte assigns a palette to a whole row or column
+        let sets = data[1] as usize;
+
+        for &set in data[2..].iter().take(sets) {
+            let number = (set & LINE_NUMBER) as usize;
+            let palette = (set >> 5) & 0b11;
+
+            for (index, attribute) in self.attributes.iter_mut().enumerate() {
+                let position = if set & LINE_HORIZONTAL != 0 {
+                    index / ATTRIBUTE_WIDTH
+                } else {
+                    index % ATTRIBUTE_WIDTH
+                };
+
+                if position == number {
+                    *attribute = palette;
+                }
+            }
+        }
+    }
+
+    fn attribute_characters(&mut self, data: &[u8]) {
+        // Assigns palettes to single 8x8 areas, starting at the given
+        // position and advancing left to right or top to bottom. Each
+        // data byte holds four palette numbers, starting at bit 7
+
+        let mut x = data[1] as usize % ATTRIBUTE_WIDTH;
+        let mut y = data[2] as usize % ATTRIBUTE_HEIGHT;
+        let count = u16::from_le_bytes([data[3], data[4]]) as usize;
+        let vertical = data[5] & 0b1 != 0;
+
+        let palettes = data[6..]
+            .iter()
+            .flat_map(|byte| (0..4).rev().map(move |shift| (byte >> (shift * 2)) & 0b11));
+
+        for palette in palettes.take(count.min(ATTRIBUTE_COUNT)) {
+            self.attributes[y * ATTRIBUTE_WIDTH + x] = palette;
+
+            if vertical {
+                y += 1;
+                if y == ATTRIBUTE_HEIGHT {
+                    y = 0;
+                    x = (x + 1) % ATTRIBUTE_WIDTH;
+                }
+            } else {
+                x += 1;
+                if x == ATTRIBUTE_WIDTH {
+                    x = 0;
+                    y = (y + 1) % ATTRIBUTE_HEIGHT;
+                }
+            }
+        }
+    }
+
+    fn apply_attribute_file(&mut self, index: u8) {
+        // Attribute files hold 2 bits per area, starting at bit 7
+        let Some(file) = self.attribute_files.get(index as usize) else {
+            return;
+        };
+
+        for (index, attribute) in self.attributes.iter_mut().enumerate() {
+            let shift = 6 - (index % 4) * 2;
+            *attribute = (file[index / 4] >> shift) & 0b11;
+        }
+    }
+
+    fn transfer(&mut self, transfer: Transfer, data: &[u8]) {
+        match transfer {
+            Transfer::BorderTiles(upper_half) => self.border.load_tiles(data, upper_half),
+            Transfer::BorderMap => self.border.load_map(data),
+            Transfer::SystemPalettes => {
+                for (palette, bytes) in self.system_palettes.iter_mut().zip(data.chunks_exact(8)) {
+                    for (color, bytes) in palette.iter_mut().zip(bytes.chunks_exact(2)) {
+                        *color = u16::from_le_bytes([bytes[0], bytes[1]]) & 0x7FFF;
+                    }
+                }
+            }
+            Transfer::AttributeFiles => {
+                for (file, bytes) in self
+                    .attribute_files
+                    .iter_mut()
+                    .zip(data.chunks_exact(ATTRIBUTE_FILE_SIZE))
+                {
+                    file.copy_from_slice(bytes);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::model::Model;
+    use crate::sgb::packet::tests::pulses;
+
+    pub fn command_pulses(command: u8, parameters: &[u8]) -> Vec<u8> {
+        // JOYP writes sending a single packet command, padded with zeros
+
+        let mut packet = [0; 16];
+        packet[0] = (command << 3) | 1;
+        packet[1..=parameters.len()].copy_from_slice(parameters);
+
+        pulses(&packet)
+    }
+
+    fn send(sgb: &mut Sgb, command: u8, parameters: &[u8]) {
+        for value in command_pulses(command, parameters) {
+            sgb.write_joypad(value);
+        }
+    }
+
+    fn attribute(sgb: &Sgb, x: usize, y: usize) -> u8 {
+        sgb.attributes[y * ATTRIBUTE_WIDTH + x]
+    }
+
+    #[test]
+    fn pal01_shares_color_0() {
+        let mut sgb = Sgb::new();
+        let colors: Vec<u8> = (1..=7u16).flat_map(|color| color.to_le_bytes()).collect();
+
+        send(&mut sgb, PAL01, &colors);
+
+        assert_eq!(sgb.palettes[0], [1, 2, 3, 4]);
+        assert_eq!(sgb.palettes[1], [1, 5, 6, 7]);
+        assert_eq!(sgb.palettes[2][0], 1);
+        assert_eq!(sgb.palettes[2][1..], DEFAULT_PALETTE[1..]);
+    }
+
+    #[test]
+    fn attr_blk_sets_inside_line_and_outside() {
+        let mut sgb = Sgb::new();
+
+        // Inside 1, line 2 and outside 3 for a block from (1,1) to (3,3)
+        send(&mut sgb, ATTR_BLK, &[1, 0b111, 0b11_10_01, 1, 1, 3, 3]);
+
+        assert_eq!(attribute(&sgb, 2, 2), 1);
+        assert_eq!(attribute(&sgb, 1, 3), 2);
+        assert_eq!(attribute(&sgb, 0, 0), 3);
+        assert_eq!(attribute(&sgb, 4, 2), 3);
+    }
+
+    #[test]
+    fn attr_blk_inside_only_includes_line() {
+        let mut sgb = Sgb::new();
+
+        send(
+            &mut sgb,
+            ATTR_BLK,
+            &[1, BLOCK_INSIDE, 0b00_00_01, 1, 1, 3, 3],
+        );
+
+        assert_eq!(attribute(&sgb, 2, 2), 1);
+        assert_eq!(attribute(&sgb, 1, 1), 1);
+        assert_eq!(attribute(&sgb, 0, 0), 0);
+    }
+
+    #[test]
+    fn attr_lin_sets_rows_and_columns() {
+        let mut sgb = Sgb::new();
+
+        send(
+            &mut sgb,
+            ATTR_LIN,
+            &[2, LINE_HORIZONTAL | (2 << 5) | 5, (1 << 5) | 3],
+        );
+
+        assert_eq!(attribute(&sgb, 0, 5), 2);
+        assert_eq!(attribute(&sgb, 19, 5), 2);
+        assert_eq!(attribute(&sgb, 3, 0), 1);
+        assert_eq!(attribute(&sgb, 3, 17), 1);
+        assert_eq!(attribute(&sgb, 0, 0), 0);
+    }
+
+    #[test]
+    fn attr_chr_wraps_to_next_row() {
+        let mut sgb = Sgb::new();
+
+        send(&mut sgb, ATTR_CHR, &[19, 0, 2, 0, 0, 0b01_10_00_00]);
+
+        assert_eq!(attribute(&sgb, 19, 0), 1);
+        assert_eq!(attribute(&sgb, 0, 1), 2);
+        assert_eq!(attribute(&sgb, 1, 1), 0);
+    }
+
+    #[test]
+    fn mlt_req_selects_number_of_players() {
+        let mut sgb = Sgb::new();
+
+        for (request, players) in [(0x01, 2), (0x03, 4), (0x00, 1)] {
+            send(&mut sgb, MLT_REQ, &[request]);
+            assert_eq!(sgb.players(), players);
+        }
+    }
+
+    #[test]
+    fn frame_is_centered_inside_border() {
+        let mut sgb = Sgb::new();
+        let gpu = Gpu::new(Model::Sgb, false);
+
+        send(&mut sgb, MASK_EN, &[2]);
+        sgb.finish_frame(&gpu);
+
+        let first = SCREEN_Y * SGB_SCREEN_WIDTH + SCREEN_X;
+        let last = first + (SCREEN_HEIGHT - 1) * SGB_SCREEN_WIDTH + SCREEN_WIDTH - 1;
+
+        assert_eq!(sgb.frame_buffer[first], 0x0000);
+        assert_eq!(sgb.frame_buffer[last], 0x0000);
+        assert_eq!(sgb.frame_buffer[first - 1], DEFAULT_PALETTE[0]);
+        assert_eq!(sgb.frame_buffer[last + 1], DEFAULT_PALETTE[0]);
+    }
+}
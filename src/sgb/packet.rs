@@ -0,0 +1,173 @@
+// Command packets are sent through P14/P15 of the joypad register.
+// Both lines low resets the transfer, P14 low sends a 0 bit and
+// P15 low a 1 bit, with both lines high in between every pulse
+const RESET: u8 = 0b00;
+const ZERO: u8 = 0b10;
+const ONE: u8 = 0b01;
+const IDLE: u8 = 0b11;
+
+const PACKET_SIZE: usize = 16;
+const PACKET_BITS: usize = PACKET_SIZE * 8;
+const MAX_PACKETS: usize = 7;
+
+pub struct PacketReceiver {
+    // Bit position within the current packet, None while
+    // waiting for the next reset pulse
+    bit: Option<usize>,
+    ready_for_pulse: bool,
+    packet: [u8; PACKET_SIZE],
+    data: Vec<u8>,
+}
+
+impl PacketReceiver {
+    pub fn new() -> Self {
+        Self {
+            bit: None,
+            ready_for_pulse: false,
+            packet: [0; PACKET_SIZE],
+            data: Vec::with_capacity(PACKET_SIZE * MAX_PACKETS),
+        }
+    }
+
+    pub fn write(&mut self, value: u8) -> Option<Vec<u8>> {
+        // Returns the data of a command once all of its packets
+        // have been received. The first byte holds the command
+        // in bits 3-7 and the number of packets in bits 0-2
+
+        let lines = (value >> 4) & 0b11;
+
+        match lines {
+            RESET => {
+                self.bit = Some(0);
+                self.packet = [0; PACKET_SIZE];
+                self.ready_for_pulse = false;
+                None
+            }
+            IDLE => {
+                self.ready_for_pulse = true;
+                None
+            }
+            ZERO | ONE if self.ready_for_pulse => {
+                self.ready_for_pulse = false;
+                let bit = self.bit?;
+                self.receive_bit(bit, lines == ONE)
+            }
+            _ => None,
+        }
+    }
+
+    fn receive_bit(&mut self, bit: usize, value: bool) -> Option<Vec<u8>> {
+        // Bytes are sent LSB first, followed by a 0 stop bit
+
+        if bit < PACKET_BITS {
+            self.packet[bit / 8] |= (value as u8) << (bit % 8);
+            self.bit = Some(bit + 1);
+            return None;
+        }
+
+        self.bit = None;
+
+        // An invalid stop bit drops the whole command
+        if value {
+            self.data.clear();
+            return None;
+        }
+
+        self.data.extend_from_slice(&self.packet);
+
+        let packets = (self.data[0] & 0b111) as usize;
+        if self.data.len() < packets.max(1) * PACKET_SIZE {
+            return None;
+        }
+
+        Some(std::mem::take(&mut self.data))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub fn pulses(packet: &[u8; PACKET_SIZE]) -> Vec<u8> {
+        // JOYP writes sending the packet, starting with a reset pulse
+
+        let mut writes = vec![RESET << 4, IDLE << 4];
+
+        let bits = packet
+            .iter()
+            .flat_map(|byte| (0..8).map(move |bit| (byte >> bit) & 0b1 != 0));
+
+        for bit in bits.chain([false]) {
+            writes.push(if bit { ONE << 4 } else { ZERO << 4 });
+            writes.push(IDLE << 4);
+        }
+
+        writes
+    }
+
+    fn send(receiver: &mut PacketReceiver, packet: &[u8; PACKET_SIZE]) -> Option<Vec<u8>> {
+        let mut data = None;
+
+        for value in pulses(packet) {
+            if let Some(command) = receiver.write(value) {
+                data = Some(command);
+            }
+        }
+
+        data
+    }
+
+    #[test]
+    fn single_packet_is_decoded_lsb_first() {
+        let mut receiver = PacketReceiver::new();
+        let mut packet = [0; PACKET_SIZE];
+        packet[0] = 0x89;
+        packet[15] = 0xA5;
+
+        assert_eq!(send(&mut receiver, &packet), Some(packet.to_vec()));
+    }
+
+    #[test]
+    fn command_waits_for_all_packets() {
+        let mut receiver = PacketReceiver::new();
+        let first = [0x22; PACKET_SIZE];
+        let second = [0x55; PACKET_SIZE];
+
+        assert_eq!(send(&mut receiver, &first), None);
+
+        let data = send(&mut receiver, &second).unwrap();
+        assert_eq!(data[..PACKET_SIZE], first);
+        assert_eq!(data[PACKET_SIZE..], second);
+    }
+
+    #[test]
+    fn invalid_stop_bit_drops_command() {
+        let mut receiver = PacketReceiver::new();
+        let packet = [0x22; PACKET_SIZE];
+
+        let mut writes = pulses(&packet);
+        let stop_bit = writes.len() - 2;
+        writes[stop_bit] = ONE << 4;
+
+        for value in writes {
+            assert_eq!(receiver.write(value), None);
+        }
+
+        // The next packet starts a new command
+        assert_eq!(
+            send(&mut receiver, &[0x00; PACKET_SIZE]),
+            Some(vec![0; PACKET_SIZE])
+        );
+    }
+
+    #[test]
+    fn pulses_without_idle_are_ignored() {
+        let mut receiver = PacketReceiver::new();
+
+        receiver.write(RESET << 4);
+        receiver.write(ONE << 4);
+        receiver.write(ONE << 4);
+
+        assert_eq!(receiver.bit, Some(0));
+    }
+}
@@ -0,0 +1,197 @@
+use crate::model::Model;
+
+pub const DIV: u16 = 0xFF04;
+pub const TIMA: u16 = 0xFF05;
+pub const TMA: u16 = 0xFF06;
+pub const TAC: u16 = 0xFF07;
+
+const TAC_ENABLE: u8 = 0b0000_0100;
+const TAC_CLOCK_SELECT: u8 = 0b0000_0011;
+const TAC_UNUSED: u8 = 0b1111_1000;
+
+pub struct Timer {
+    // DIV is the upper byte of this 16-bit counter,
+    // which is incremented every T-cycle
+    divider: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+    // TIMA overflowed and is reloaded from TMA on the next M-cycle
+    overflow: bool,
+    // TIMA was reloaded from TMA during the current M-cycle
+    reloading: bool,
+}
+
+impl Timer {
+    pub fn new(model: Model) -> Self {
+        // The internal divider runs during the boot ROM, so its
+        // phase depends on how long the boot sequence of each model takes
+        let divider = match model {
+            Model::Dmg0 => 0x1830,
+            Model::Dmg | Model::Mgb => 0xABCC,
+            Model::Sgb | Model::Sgb2 => 0xD85C,
+            Model::Cgb => 0x1EA0,
+        };
+
+        Self {
+            divider,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            overflow: false,
+            reloading: false,
+        }
+    }
+
+    pub fn tick(&mut self, cycles: u8) -> bool {
+        // Advances the timer one M-cycle at a time and
+        // returns whether the timer interrupt was requested
+
+        let mut interrupt = false;
+
+        for _ in 0..cycles / 4 {
+            self.reloading = false;
+
+            // TIMA reads as 0x00 for one M-cycle after an overflow,
+            // then gets reloaded from TMA and requests the interrupt
+            if self.overflow {
+                self.overflow = false;
+                self.reloading = true;
+                self.tima = self.tma;
+                interrupt = true;
+            }
+
+            self.set_divider(self.divider.wrapping_add(4));
+        }
+
+        interrupt
+    }
+
+    pub fn read_byte(&self, address: u16) -> u8 {
+        match address {
+            DIV => (self.divider >> 8) as u8,
+            TIMA => self.tima,
+            TMA => self.tma,
+            TAC => self.tac | TAC_UNUSED,
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn write_byte(&mut self, address: u16, value: u8) {
+        match address {
+            // Writing any value resets the whole internal divider
+            DIV => self.set_divider(0),
+            TIMA => {
+                // Writes during the reload cycle are ignored, writes
+                // during the overflow cycle cancel the reload
+                if !self.reloading {
+                    self.tima = value;
+                    self.overflow = false;
+                }
+            }
+            TMA => {
+                self.tma = value;
+
+                if self.reloading {
+                    self.tima = value;
+                }
+            }
+            TAC => {
+                // Disabling the timer or switching the clock select
+                // can cause a falling edge, which increments TIMA
+                let signal = self.timer_signal();
+                self.tac = value & !TAC_UNUSED;
+
+                if signal && !self.timer_signal() {
+                    self.increment_tima();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn set_divider(&mut self, value: u16) {
+        // TIMA is incremented on the falling edge of the
+        // divider bit selected by TAC, ANDed with the enable bit
+
+        let signal = self.timer_signal();
+        self.divider = value;
+
+        if signal && !self.timer_signal() {
+            self.increment_tima();
+        }
+    }
+
+    fn timer_signal(&self) -> bool {
+        let bit = match self.tac & TAC_CLOCK_SELECT {
+            0b00 => 9, // 4096 Hz
+            0b01 => 3, // 262144 Hz
+            0b10 => 5, // 65536 Hz
+            _ => 7,    // 16384 Hz
+        };
+
+        self.tac & TAC_ENABLE != 0 && (self.divider >> bit) & 0b1 != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (result, overflow) = self.tima.overflowing_add(1);
+
+        self.tima = result;
+        self.overflow = overflow;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timer(tac: u8) -> Timer {
+        // Starts with the divider reset. TAC 0b101 selects the
+        // 262144 Hz clock, which increments TIMA every 16 T-cycles
+
+        let mut timer = Timer::new(Model::Dmg);
+        timer.write_byte(DIV, 0);
+        timer.write_byte(TAC, tac);
+
+        timer
+    }
+
+    #[test]
+    fn tima_counts_at_selected_rate() {
+        let mut timer = timer(0b101);
+
+        for _ in 0..3 {
+            timer.tick(4);
+        }
+        assert_eq!(timer.read_byte(TIMA), 0);
+
+        timer.tick(4);
+        assert_eq!(timer.read_byte(TIMA), 1);
+    }
+
+    #[test]
+    fn div_reset_on_falling_edge_increments_tima() {
+        let mut timer = timer(0b101);
+
+        timer.tick(8);
+        timer.write_byte(DIV, 0);
+
+        assert_eq!(timer.read_byte(TIMA), 1);
+        assert_eq!(timer.read_byte(DIV), 0);
+    }
+
+    #[test]
+    fn overflow_reloads_tma_one_m_cycle_later() {
+        let mut timer = timer(0b101);
+        timer.write_byte(TMA, 0xAB);
+        timer.write_byte(TIMA, 0xFF);
+
+        for _ in 0..4 {
+            assert!(!timer.tick(4));
+        }
+        assert_eq!(timer.read_byte(TIMA), 0x00);
+
+        assert!(timer.tick(4));
+        assert_eq!(timer.read_byte(TIMA), 0xAB);
+    }
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use gemboi::machine::{LoadError, Machine};
+use gemboi::Model;
+
+fn boot_rom() -> Vec<u8> {
+    // Unmaps itself with its last instruction and falls through to 0x0100
+
+    let mut boot_rom = vec![0; 0x100];
+    boot_rom[0x00..0x02].copy_from_slice(&[0x3E, 0x01]); // LD A,0x01
+    boot_rom[0xFE..0x100].copy_from_slice(&[0xE0, 0x50]); // LDH (0x50),A
+
+    boot_rom
+}
+
+#[test]
+fn boot_rom_is_unmapped_by_writing_ff50() {
+    let mut rom = common::rom(&[
+        0x3E, 0x42, // LD A,0x42
+        0xEA, 0x00, 0xC0, // LD (0xC000),A
+        0x18, 0xFE, // JR -2
+    ]);
+    rom[0x0000] = 0xAA;
+
+    let mut machine = Machine::with_boot_rom(rom, boot_rom(), Model::Dmg).unwrap();
+    assert_eq!(machine.read_byte(0x0000), 0x3E);
+
+    machine.step_frame();
+    assert_eq!(machine.read_byte(0x0000), 0xAA);
+    assert_eq!(machine.read_byte(0xC000), 0x42);
+}
+
+#[test]
+fn boot_rom_size_is_checked() {
+    let result = Machine::with_boot_rom(common::rom(&[]), vec![0; 0x200], Model::Cgb);
+
+    assert_eq!(result.err(), Some(LoadError::BootRomSize(0x200)));
+}
+
+#[test]
+fn without_boot_rom_cartridge_is_mapped() {
+    let mut rom = common::rom(&[]);
+    rom[0x0000] = 0xAA;
+
+    let machine = Machine::new(rom).unwrap();
+    assert_eq!(machine.read_byte(0x0000), 0xAA);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use gemboi::machine::Machine;
+
+fn cgb_rom(program: &[u8]) -> Vec<u8> {
+    let mut rom = common::rom(program);
+    rom[0x0143] = 0x80;
+
+    rom
+}
+
+fn count_per_frame(speed_switch: &[u8]) -> u16 {
+    // Counts loop iterations in BC during one frame, after an optional
+    // speed switch. Each iteration takes 52 T-cycles in normal speed
+
+    let mut program = speed_switch.to_vec();
+    program.extend_from_slice(&[
+        0x01, 0x00, 0x00, // LD BC,0x0000
+        0x03, // INC BC
+        0x78, // LD A,B
+        0xE0, 0x80, // LDH (0x80),A
+        0x79, // LD A,C
+        0xE0, 0x81, // LDH (0x81),A
+        0x18, 0xF7, // JR -9
+    ]);
+
+    let mut machine = Machine::new(cgb_rom(&program)).unwrap();
+    machine.step_frame();
+
+    let count = |machine: &Machine| {
+        u16::from_be_bytes([machine.read_byte(0xFF80), machine.read_byte(0xFF81)])
+    };
+
+    let start = count(&machine);
+    machine.step_frame();
+
+    count(&machine) - start
+}
+
+#[test]
+fn double_speed_runs_twice_the_cycles_per_frame() {
+    let normal = count_per_frame(&[]);
+    let double = count_per_frame(&[
+        0x3E, 0x01, // LD A,0x01
+        0xE0, 0x4D, // LDH (KEY1),A
+        0x10, 0x00, // STOP
+    ]);
+
+    assert!((1350..=1351).contains(&normal));
+    assert!((2 * normal - 1..=2 * normal + 1).contains(&double));
+}
+
+#[test]
+fn frame_buffer_uses_background_palette() {
+    // Color 0 of background palette 0 is set to pure blue
+    let mut machine = Machine::new(cgb_rom(&[
+        0x3E, 0x80, // LD A,0x80
+        0xE0, 0x68, // LDH (BCPS),A
+        0x3E, 0x00, // LD A,0x00
+        0xE0, 0x69, // LDH (BCPD),A
+        0x3E, 0x7C, // LD A,0x7C
+        0xE0, 0x69, // LDH (BCPD),A
+        0x18, 0xFE, // JR -2
+    ]))
+    .unwrap();
+
+    machine.step_frame();
+    machine.step_frame();
+
+    assert!(machine.frame_buffer().iter().all(|&color| color == 0x7C00));
+}
@@ -0,0 +1,11 @@
+// Builds a 32 KiB ROM-only cartridge which jumps to the
+// program at 0x0150 and has the header at its default values
+pub fn rom(program: &[u8]) -> Vec<u8> {
+    let mut rom = vec![0; 0x8000];
+
+    // NOP; JP 0x0150
+    rom[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+    rom[0x0150..0x0150 + program.len()].copy_from_slice(program);
+
+    rom
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use gemboi::machine::{LinkedMachines, Machine};
+
+const SB: u16 = 0xFF01;
+const SC: u16 = 0xFF02;
+const IF: u16 = 0xFF0F;
+const SERIAL_INTERRUPT: u8 = 0b1000;
+
+fn transfer_rom(data: u8, control: u8) -> Vec<u8> {
+    // Starts a transfer and waits for it to finish, with
+    // interrupts disabled so IF keeps the serial request
+
+    common::rom(&[
+        0x3E, data, // LD A,data
+        0xE0, 0x01, // LDH (SB),A
+        0x3E, control, // LD A,control
+        0xE0, 0x02, // LDH (SC),A
+        0xF0, 0x02, // LDH A,(SC)
+        0xE6, 0x80, // AND 0x80
+        0x20, 0xFA, // JR NZ,-6
+        0x18, 0xFE, // JR -2
+    ])
+}
+
+#[test]
+fn transfer_exchanges_bytes() {
+    let slave = Machine::new(transfer_rom(0x42, 0x80)).unwrap();
+    let master = Machine::new(transfer_rom(0x99, 0x81)).unwrap();
+    let mut linked = LinkedMachines::new(slave, master);
+
+    for _ in 0..2 {
+        linked.step_frame();
+    }
+
+    assert_transfer_finished(linked.first(), 0x99);
+    assert_transfer_finished(linked.second(), 0x42);
+}
+
+fn assert_transfer_finished(machine: &Machine, received: u8) {
+    assert_eq!(machine.read_byte(SB), received);
+    assert_eq!(machine.read_byte(SC) & 0x80, 0);
+    assert_ne!(machine.read_byte(IF) & SERIAL_INTERRUPT, 0);
+}
+
+#[test]
+fn transfer_without_slave_reads_high() {
+    let idle = Machine::new(common::rom(&[0x18, 0xFE])).unwrap();
+    let master = Machine::new(transfer_rom(0x99, 0x81)).unwrap();
+    let mut linked = LinkedMachines::new(idle, master);
+
+    linked.step_frame();
+
+    let master = linked.second();
+    assert_eq!(master.read_byte(SB), 0xFF);
+    assert_ne!(master.read_byte(IF) & SERIAL_INTERRUPT, 0);
+    assert_eq!(linked.first().read_byte(IF) & SERIAL_INTERRUPT, 0);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use gemboi::machine::Machine;
+use gemboi::Model;
+
+const PROGRAM: [u8; 10] = [
+    0xE0, 0x80, // LDH (0x80),A
+    0xF0, 0x04, // LDH A,(DIV)
+    0xE0, 0x81, // LDH (0x81),A
+    0x78, // LD A,B
+    0xE0, 0x82, // LDH (0x82),A
+    0x76, // HALT
+];
+
+fn boot_state(machine: &mut Machine) -> [u8; 3] {
+    // A, DIV and B right after the boot ROM handed over
+    machine.step_frame();
+
+    [
+        machine.read_byte(0xFF80),
+        machine.read_byte(0xFF81),
+        machine.read_byte(0xFF82),
+    ]
+}
+
+#[test]
+fn registers_and_div_depend_on_model() {
+    let expected = [
+        (Model::Dmg0, [0x01, 0x18, 0xFF]),
+        (Model::Dmg, [0x01, 0xAB, 0x00]),
+        (Model::Mgb, [0xFF, 0xAB, 0x00]),
+        (Model::Sgb, [0x01, 0xD8, 0x00]),
+        (Model::Sgb2, [0xFF, 0xD8, 0x00]),
+        (Model::Cgb, [0x11, 0x1E, 0x00]),
+    ];
+
+    for (model, state) in expected {
+        let mut machine = Machine::with_model(common::rom(&PROGRAM), model).unwrap();
+        assert_eq!(machine.read_byte(0xFF40), 0x91, "LCDC on {:?}", model);
+
+        assert_eq!(boot_state(&mut machine), state, "{:?}", model);
+    }
+}
+
+#[test]
+fn cgb_games_run_on_cgb() {
+    let mut rom = common::rom(&PROGRAM);
+    rom[0x0143] = 0x80;
+
+    let mut machine = Machine::new(rom).unwrap();
+    assert_eq!(boot_state(&mut machine)[0], 0x11);
+}
+
+#[test]
+fn cgb_leaves_title_checksum_for_nintendo_dmg_games() {
+    let mut rom = common::rom(&PROGRAM);
+    rom[0x0134..0x0137].copy_from_slice(b"GEM");
+    rom[0x014B] = 0x01;
+
+    let mut machine = Machine::with_model(rom, Model::Cgb).unwrap();
+    let checksum = b'G'.wrapping_add(b'E').wrapping_add(b'M');
+    assert_eq!(boot_state(&mut machine)[2], checksum);
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use gemboi::machine::Machine;
+use gemboi::SCREEN_WIDTH;
+
+const WHITE: u16 = 0x7FFF;
+const BLACK: u16 = 0x0000;
+
+// Tile 1 gets a black top row, color 3 with the post-boot BGP
+const BLACK_TILE_ROW: [u8; 7] = [
+    0x21, 0x10, 0x80, // LD HL,0x8010
+    0x3E, 0xFF, // LD A,0xFF
+    0x22, // LD (HL+),A
+    0x22, // LD (HL+),A
+];
+
+fn run(setup: &[u8]) -> Machine {
+    // Sets up VRAM with the LCD off, then turns it back on
+    // with the LCDC value in A
+
+    let mut program = vec![
+        0xAF, // XOR A
+        0xE0, 0x40, // LDH (LCDC),A
+    ];
+    program.extend_from_slice(&BLACK_TILE_ROW);
+    program.extend_from_slice(setup);
+    program.extend_from_slice(&[
+        0xE0, 0x40, // LDH (LCDC),A
+        0x18, 0xFE, // JR -2
+    ]);
+
+    let mut machine = Machine::new(common::rom(&program)).unwrap();
+    for _ in 0..3 {
+        machine.step_frame();
+    }
+
+    machine
+}
+
+#[test]
+fn background_tile() {
+    let machine = run(&[
+        0x3E, 0x01, // LD A,0x01
+        0xEA, 0x00, 0x98, // LD (0x9800),A
+        0x3E, 0x91, // LD A,0x91
+    ]);
+    let frame = machine.frame_buffer();
+
+    assert_eq!(frame[0..8], [BLACK; 8]);
+    assert_eq!(frame[8], WHITE);
+    assert_eq!(frame[SCREEN_WIDTH], WHITE);
+}
+
+#[test]
+fn window_covers_background() {
+    let machine = run(&[
+        0x3E, 0x01, // LD A,0x01
+        0xEA, 0x00, 0x9C, // LD (0x9C00),A
+        0x3E, 0x57, // LD A,87
+        0xE0, 0x4B, // LDH (WX),A
+        0xAF, // XOR A
+        0xE0, 0x4A, // LDH (WY),A
+        0x3E, 0xF1, // LD A,0xF1
+    ]);
+    let frame = machine.frame_buffer();
+
+    assert_eq!(frame[0..80], [WHITE; 80]);
+    assert_eq!(frame[80..88], [BLACK; 8]);
+    assert_eq!(frame[88], WHITE);
+}
+
+#[test]
+fn sprite_copied_by_oam_dma() {
+    // The DMA routine runs from HRAM, waiting 160 M-cycles
+    let routine = [
+        0xE0, 0x46, // LDH (DMA),A
+        0x3E, 0x28, // LD A,40
+        0x3D, // DEC A
+        0x20, 0xFD, // JR NZ,-3
+        0xC9, // RET
+    ];
+
+    let mut setup = vec![
+        // Sprite 0 at the top left corner with tile 1
+        0x21, 0x00, 0xC0, // LD HL,0xC000
+        0x3E, 0x10, 0x22, // LD A,16; LD (HL+),A
+        0x3E, 0x08, 0x22, // LD A,8; LD (HL+),A
+        0x3E, 0x01, 0x22, // LD A,1; LD (HL+),A
+        0xAF, 0x22, // XOR A; LD (HL+),A
+        0x3E, 0xE4, // LD A,0xE4
+        0xE0, 0x48, // LDH (OBP0),A
+        0x21, 0x80, 0xFF, // LD HL,0xFF80
+    ];
+    for byte in routine {
+        setup.extend_from_slice(&[0x3E, byte, 0x22]); // LD A,byte; LD (HL+),A
+    }
+    setup.extend_from_slice(&[
+        0x3E, 0xC0, // LD A,0xC0
+        0xCD, 0x80, 0xFF, // CALL 0xFF80
+        0x3E, 0x93, // LD A,0x93
+    ]);
+
+    let machine = run(&setup);
+
+    assert_eq!(machine.read_byte(0xFE00), 0x10);
+    assert_eq!(machine.read_byte(0xFE02), 0x01);
+    assert_eq!(machine.frame_buffer()[0..8], [BLACK; 8]);
+    assert_eq!(machine.frame_buffer()[SCREEN_WIDTH], WHITE);
+}